@@ -0,0 +1,154 @@
+//! # Parallel Walk Benchmark
+//!
+//! Builds a synthetic tree shaped like a project checkout: a few small top-level
+//! directories next to one deep, wide `node_modules`. Before the walker split work
+//! at every directory level, each top-level child was walked by a single blocking
+//! task, so that one child kept a single thread busy while the others sat idle.
+//!
+//! The tree is scanned with [`DiskAnalyzer`] in this process, so only the scan is
+//! timed: once the old way, one thread per top-level child, and then on the
+//! work-stealing pool with 1, 2, 4 and one thread per CPU. The median wall-clock
+//! time of each is printed with the speedup over the old walk.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! Run with `cargo bench --bench walker`. The speedup depends on the number of
+//! CPUs and on whether the filesystem metadata is already cached in memory.
+
+use disk_cleaner_rs::analyzer::{DiskAnalyzer, SizeMode};
+use disk_cleaner_rs::tree::DirTree;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+/// Levels below `node_modules`
+const DEPTH: u32 = 5;
+/// Subdirectories of every directory in `node_modules`
+const FANOUT: u32 = 6;
+/// Files in every directory
+const FILES_PER_DIR: u32 = 8;
+/// Small top-level siblings of `node_modules`
+const SIBLINGS: u32 = 4;
+/// Timed scans per configuration; the median is reported
+const RUNS: usize = 5;
+
+fn main() {
+    let temp_dir = TempDir::new().expect("temporary directory");
+    let root = temp_dir.path();
+    let directories = build_tree(&root.join("node_modules"), DEPTH);
+    for sibling in 0..SIBLINGS {
+        build_tree(&root.join(format!("src{}", sibling)), 1);
+    }
+    let files = (directories + SIBLINGS * (FANOUT + 1)) * FILES_PER_DIR;
+    println!(
+        "Synthetic tree: {} directories in node_modules, {} files in total",
+        directories, files
+    );
+
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut thread_counts = vec![1, 2, 4, cpus];
+    thread_counts.retain(|&threads| threads <= cpus.max(2));
+    thread_counts.sort_unstable();
+    thread_counts.dedup();
+
+    let runtime = Runtime::new().expect("tokio runtime");
+    let scan = |threads: usize| scan(&runtime, root, threads);
+
+    // One untimed scan so every run finds the metadata equally cached
+    scan(cpus);
+
+    let baseline = median(|| scan_per_child(root));
+    println!("{:>10}  {:>10}  {:>8}", "walk", "median", "speedup");
+    print_row("per child", baseline, baseline);
+    for threads in thread_counts {
+        let label = format!("{} threads", threads);
+        print_row(&label, median(|| scan(threads)), baseline);
+    }
+    if cpus == 1 {
+        println!("Only one CPU is available, so no speedup is possible here.");
+    }
+}
+
+fn print_row(label: &str, time: Duration, baseline: Duration) {
+    println!(
+        "{:>10}  {:>8.1}ms  {:>7.2}x",
+        label,
+        time.as_secs_f64() * 1000.0,
+        baseline.as_secs_f64() / time.as_secs_f64()
+    );
+}
+
+/// Creates `dir` with `FILES_PER_DIR` files and `FANOUT` subdirectories, `depth`
+/// levels deep, and returns the number of directories created
+fn build_tree(dir: &Path, depth: u32) -> u32 {
+    fs::create_dir_all(dir).expect("create directory");
+    for file in 0..FILES_PER_DIR {
+        fs::write(dir.join(format!("file{}.js", file)), [b'x'; 100]).expect("create file");
+    }
+    if depth == 0 {
+        return 1;
+    }
+    1 + (0..FANOUT)
+        .map(|child| build_tree(&dir.join(format!("pkg{}", child)), depth - 1))
+        .sum::<u32>()
+}
+
+/// Median time of `RUNS` calls to `scan`
+fn median(mut scan: impl FnMut() -> Duration) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS).map(|_| scan()).collect();
+    times.sort_unstable();
+    times[RUNS / 2]
+}
+
+/// Time of one complete scan of `root` by the analyzer with `threads` threads
+fn scan(runtime: &Runtime, root: &Path, threads: usize) -> Duration {
+    let analyzer = DiskAnalyzer::new(1).with_threads(NonZeroUsize::new(threads).unwrap());
+    let start = Instant::now();
+    let tree = runtime
+        .block_on(analyzer.analyze_tree(root))
+        .expect("scan failed");
+    let elapsed = start.elapsed();
+    assert!(tree.node(tree.root()).file_count > 0);
+    elapsed
+}
+
+/// Time of one complete scan of `root` the old way: every top-level child on a
+/// thread of its own, which walks it alone
+fn scan_per_child(root: &Path) -> Duration {
+    let start = Instant::now();
+    let mut tree = DirTree::new(root.to_path_buf());
+    let subtrees: Vec<DirTree> = thread::scope(|scope| {
+        let walks: Vec<_> = fs::read_dir(root)
+            .expect("read benchmark root")
+            .map(|entry| {
+                let path = entry.expect("read benchmark root").path();
+                scope.spawn(move || {
+                    let pool = rayon::ThreadPoolBuilder::new()
+                        .num_threads(1)
+                        .build()
+                        .expect("single-thread pool");
+                    let mut subtree = pool.install(|| DirTree::scan(&path));
+                    subtree.resolve_hard_links();
+                    subtree.sort_by_size(SizeMode::Apparent);
+                    subtree
+                })
+            })
+            .collect();
+        walks
+            .into_iter()
+            .map(|walk| walk.join().expect("walk thread"))
+            .collect()
+    });
+    let root_id = tree.root();
+    for subtree in subtrees {
+        tree.graft(root_id, subtree);
+    }
+    tree.resolve_hard_links();
+    tree.sort_by_size(SizeMode::Apparent);
+    start.elapsed()
+}
@@ -0,0 +1,373 @@
+//! # Deletability
+//!
+//! Decides whether the current user may remove an entry the way the kernel will,
+//! so that entries bound to fail are reported up front with the reason instead of
+//! failing halfway through a cleanup.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Unix Rules
+//!
+//! - Removing or renaming an entry changes the directory holding it, which takes
+//!   write and search (execute) permission on that directory. The entry's own
+//!   permission bits do not matter.
+//! - In a sticky directory such as `/tmp`, only the owner of the entry or of the
+//!   directory may do so.
+//! - Deleting a directory first deletes everything in it, so the same holds at
+//!   every level below, and every directory must be readable to be listed.
+//! - Moving a directory into another one rewrites its `..` entry, which takes write
+//!   permission on the directory itself.
+//!
+//! As with `access(2)`, the permission class is chosen from the effective user and
+//! group IDs and the supplementary groups, and the superuser may do anything. ACLs
+//! and flags such as immutable are not evaluated; they still fail the removal itself.
+
+use serde::Serialize;
+use std::fmt;
+#[cfg(unix)]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How an entry is going to be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    /// Deleted together with everything inside it
+    Delete,
+    /// Renamed into another directory, such as the trash
+    Move,
+}
+
+/// Why the current user cannot remove an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenyReason {
+    /// No write permission on the directory holding the entry
+    ParentNotWritable,
+    /// No search permission on the directory holding the entry
+    ParentNotSearchable,
+    /// The directory holding the entry is sticky, and the user owns neither
+    StickyNotOwner,
+    /// A directory whose contents cannot be listed to delete them
+    NotReadable,
+    /// A directory that cannot be written, which moving it requires
+    NotWritable,
+    /// The entry has the read-only attribute
+    #[cfg(windows)]
+    ReadOnly,
+}
+
+/// An entry that cannot be removed, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Denial {
+    pub reason: DenyReason,
+    /// The entry that cannot be removed: the requested one or something inside it
+    pub path: PathBuf,
+}
+
+impl fmt::Display for Denial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match self.reason {
+            DenyReason::ParentNotWritable => {
+                write!(f, "no write permission on the directory holding '{}'", path)
+            }
+            DenyReason::ParentNotSearchable => {
+                write!(
+                    f,
+                    "no search permission on the directory holding '{}'",
+                    path
+                )
+            }
+            DenyReason::StickyNotOwner => {
+                write!(
+                    f,
+                    "'{}' belongs to another user in a sticky directory",
+                    path
+                )
+            }
+            DenyReason::NotReadable => write!(f, "cannot list the directory '{}'", path),
+            DenyReason::NotWritable => write!(f, "no write permission on the directory '{}'", path),
+            #[cfg(windows)]
+            DenyReason::ReadOnly => write!(f, "'{}' is read-only", path),
+        }
+    }
+}
+
+/// Why the current user cannot remove `path` by `removal`, or `None` if they can.
+///
+/// An entry that does not exist or cannot be examined is not denied here; removing
+/// it reports what is wrong.
+pub fn denial(path: &Path, removal: Removal) -> Option<Denial> {
+    #[cfg(unix)]
+    {
+        unix::check(path, removal, &unix::Credentials::current()).err()
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+
+        let _ = removal;
+        let attributes = path.metadata().ok()?.file_attributes();
+        ((attributes & FILE_ATTRIBUTE_READONLY) != 0).then(|| Denial {
+            reason: DenyReason::ReadOnly,
+            path: path.to_path_buf(),
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (path, removal);
+        None
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    const READ: u32 = 0o4;
+    const WRITE: u32 = 0o2;
+    const SEARCH: u32 = 0o1;
+    const STICKY: u32 = 0o1000;
+
+    /// The identity permissions are checked against.
+    #[derive(Debug, Clone)]
+    pub struct Credentials {
+        pub uid: u32,
+        /// Effective group and supplementary groups
+        pub groups: Vec<u32>,
+    }
+
+    impl Credentials {
+        /// Effective IDs of this process
+        pub fn current() -> Self {
+            // SAFETY: these calls only read the credentials of the calling process
+            let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+            let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+            let mut groups = vec![0; count.max(0) as usize];
+            // SAFETY: `groups` has room for the `count` IDs asked for
+            let written = unsafe { libc::getgroups(count.max(0), groups.as_mut_ptr()) };
+            groups.truncate(written.max(0) as usize);
+            groups.push(gid);
+            Self { uid, groups }
+        }
+
+        fn is_superuser(&self) -> bool {
+            self.uid == 0
+        }
+
+        /// Whether all permission `bits` are granted on the directory `metadata`
+        fn allows(&self, metadata: &fs::Metadata, bits: u32) -> bool {
+            if self.is_superuser() {
+                return true;
+            }
+            let shift = if metadata.uid() == self.uid {
+                6
+            } else if self.groups.contains(&metadata.gid()) {
+                3
+            } else {
+                0
+            };
+            (metadata.mode() >> shift) & bits == bits
+        }
+
+        fn owns(&self, metadata: &fs::Metadata) -> bool {
+            self.is_superuser() || metadata.uid() == self.uid
+        }
+    }
+
+    /// Whether `credentials` may remove `path` by `removal`
+    pub fn check(path: &Path, removal: Removal, credentials: &Credentials) -> Result<(), Denial> {
+        let parent = match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => return Ok(()),
+        };
+        let (Ok(metadata), Ok(parent_metadata)) = (path.symlink_metadata(), fs::metadata(parent))
+        else {
+            return Ok(());
+        };
+        check_entry(path, &metadata, &parent_metadata, credentials)?;
+        if !metadata.is_dir() {
+            return Ok(());
+        }
+        match removal {
+            Removal::Delete => check_contents(path, metadata, credentials),
+            Removal::Move if credentials.allows(&metadata, WRITE) => Ok(()),
+            Removal::Move => Err(Denial {
+                reason: DenyReason::NotWritable,
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Whether the entry at `path` may be unlinked from the directory `parent`
+    fn check_entry(
+        path: &Path,
+        metadata: &fs::Metadata,
+        parent: &fs::Metadata,
+        credentials: &Credentials,
+    ) -> Result<(), Denial> {
+        let reason = if !credentials.allows(parent, WRITE) {
+            DenyReason::ParentNotWritable
+        } else if !credentials.allows(parent, SEARCH) {
+            DenyReason::ParentNotSearchable
+        } else if parent.mode() & STICKY != 0
+            && !credentials.owns(metadata)
+            && !credentials.owns(parent)
+        {
+            DenyReason::StickyNotOwner
+        } else {
+            return Ok(());
+        };
+        Err(Denial {
+            reason,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Whether everything below the directory `root` may be deleted
+    ///
+    /// Filesystems mounted below `root` are not entered; removing their mount
+    /// point fails on its own.
+    fn check_contents(
+        root: &Path,
+        metadata: fs::Metadata,
+        credentials: &Credentials,
+    ) -> Result<(), Denial> {
+        let device = metadata.dev();
+        let mut pending = vec![(root.to_path_buf(), metadata)];
+        while let Some((dir, metadata)) = pending.pop() {
+            if !credentials.allows(&metadata, READ | SEARCH) {
+                return Err(Denial {
+                    reason: DenyReason::NotReadable,
+                    path: dir,
+                });
+            }
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                // Symbolic links are removed, never followed
+                let Ok(child_metadata) = entry.metadata() else {
+                    continue;
+                };
+                let child = entry.path();
+                check_entry(&child, &child_metadata, &metadata, credentials)?;
+                if child_metadata.is_dir() && child_metadata.dev() == device {
+                    pending.push((child, child_metadata));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::TempDir;
+
+        /// Someone who is neither the owner nor in the group of the test files
+        fn stranger() -> Credentials {
+            Credentials {
+                uid: 4242,
+                groups: vec![4242],
+            }
+        }
+
+        fn chmod(path: &Path, mode: u32) {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        fn reason(path: &Path, removal: Removal, credentials: &Credentials) -> Option<DenyReason> {
+            check(path, removal, credentials).err().map(|d| d.reason)
+        }
+
+        #[test]
+        fn test_parent_permissions_decide() {
+            let temp_dir = TempDir::new().unwrap();
+            let dir = temp_dir.path().join("dir");
+            fs::create_dir(&dir).unwrap();
+            let file = dir.join("file.txt");
+            fs::write(&file, b"data").unwrap();
+            // The file's own mode does not matter
+            chmod(&file, 0o444);
+
+            chmod(&dir, 0o777);
+            assert_eq!(reason(&file, Removal::Delete, &stranger()), None);
+            chmod(&dir, 0o755);
+            assert_eq!(
+                reason(&file, Removal::Delete, &stranger()),
+                Some(DenyReason::ParentNotWritable)
+            );
+            chmod(&dir, 0o776);
+            assert_eq!(
+                reason(&file, Removal::Delete, &stranger()),
+                Some(DenyReason::ParentNotSearchable)
+            );
+
+            // Group permissions apply to members of the directory's group
+            chmod(&dir, 0o770);
+            let member = Credentials {
+                uid: 4242,
+                groups: vec![4242, fs::metadata(&dir).unwrap().gid()],
+            };
+            assert_eq!(reason(&file, Removal::Delete, &member), None);
+            assert_eq!(
+                reason(&file, Removal::Delete, &stranger()),
+                Some(DenyReason::ParentNotWritable)
+            );
+
+            // In a sticky directory, only owners may remove entries
+            chmod(&dir, 0o1777);
+            assert_eq!(
+                reason(&file, Removal::Delete, &stranger()),
+                Some(DenyReason::StickyNotOwner)
+            );
+            let superuser = Credentials {
+                uid: 0,
+                groups: vec![0],
+            };
+            assert_eq!(reason(&file, Removal::Delete, &superuser), None);
+            chmod(&dir, 0o755);
+        }
+
+        #[test]
+        fn test_directories_need_their_contents_removable() {
+            let temp_dir = TempDir::new().unwrap();
+            let top = temp_dir.path().join("top");
+            fs::create_dir_all(top.join("inner")).unwrap();
+            fs::write(top.join("inner/file.txt"), b"data").unwrap();
+            chmod(temp_dir.path(), 0o777);
+            chmod(&top, 0o777);
+
+            chmod(&top.join("inner"), 0o755);
+            let denial = check(&top, Removal::Delete, &stranger()).unwrap_err();
+            assert_eq!(denial.reason, DenyReason::ParentNotWritable);
+            assert_eq!(denial.path, top.join("inner/file.txt"));
+
+            chmod(&top.join("inner"), 0o733);
+            let denial = check(&top, Removal::Delete, &stranger()).unwrap_err();
+            assert_eq!(denial.reason, DenyReason::NotReadable);
+            assert_eq!(denial.path, top.join("inner"));
+
+            chmod(&top.join("inner"), 0o777);
+            assert_eq!(reason(&top, Removal::Delete, &stranger()), None);
+
+            // Moving ignores the contents but rewrites the directory's `..`
+            chmod(&top.join("inner"), 0o755);
+            assert_eq!(reason(&top, Removal::Move, &stranger()), None);
+            chmod(&top, 0o755);
+            assert_eq!(
+                reason(&top, Removal::Move, &stranger()),
+                Some(DenyReason::NotWritable)
+            );
+            chmod(temp_dir.path(), 0o700);
+        }
+    }
+}
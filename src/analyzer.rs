@@ -10,8 +10,18 @@
 //! ## Key Features
 //!
 //! - **Async Processing**: Non-blocking directory traversal for large filesystems
+//! - **Parallel Walk**: Directories at every level are spread over a work-stealing pool
+//! - **Progress Reporting**: Live counters of files, bytes, pending directories and errors
+//! - **Tree Model**: A single traversal produces a [`DirTree`] that every view is built from
+//! - **Complete Totals**: Sizes are always fully recursive; depth only limits what is shown
 //! - **Size Calculation**: Accurate byte-level size reporting with human-readable formatting
+//! - **Allocated vs Apparent**: Tracks the bytes really used on disk next to the file length
+//! - **Hard-Link Aware**: Each inode is counted once; bytes linked from elsewhere are "shared"
+//! - **Incremental Rescans**: With a [`ScanCache`], unchanged directories are not read again
 //! - **Flexible Filtering**: Filter by minimum size and entry type (files/directories)
+//! - **Path Filters**: Excluded subtrees are never walked, and their files are counted apart
+//! - **Git Status**: Inside a work tree, entries are tagged tracked, untracked or ignored
+//! - **Mount Points**: Other filesystems are marked with their type, or skipped entirely
 //! - **Error Resilience**: Graceful handling of permission errors and inaccessible files
 //! - **Cross-Platform**: Works reliably on Windows, Linux, and macOS
 //!
@@ -20,17 +30,32 @@
 //! ```rust
 //! use analyzer::DiskAnalyzer;
 //!
-//! let analyzer = DiskAnalyzer::new(2); // Depth limit of 2
-//! let entries = analyzer.analyze_directory("/path/to/scan").await?;
+//! let analyzer = DiskAnalyzer::new(2); // Show two levels
+//! let tree = analyzer.analyze_tree("/path/to/scan").await?;
+//! let entries = analyzer.visible_entries(&tree);
 //! let large_files = analyzer.filter_entries(&entries, Some(1_000_000)); // >1MB
 //! ```
 
-use anyhow::Result;
+use crate::cache::{CachedEntry, DirectoryCache, ScanCache};
+use crate::filter::{ExcludedTotals, PathFilter};
+use crate::git::{self, GitInfo, RepoStatus};
+use crate::mounts::{self, MountTable};
+use crate::ncdu;
+use crate::platform::PlatformUtils;
+use crate::progress::ScanProgress;
+use crate::serialize;
+use crate::tree::DirTree;
+use crate::walker::WalkOptions;
+use anyhow::{Context, Result};
 use humansize::{format_size, DECIMAL};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use tokio::task;
-use walkdir::WalkDir;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 /// Represents a filesystem entry (file or directory) with comprehensive metadata.
 ///
@@ -42,7 +67,18 @@ use walkdir::WalkDir;
 /// * `path` - The full filesystem path to this entry
 /// * `size_bytes` - Size in bytes (for files: file size, for directories: total recursive size)
 /// * `size_human` - Human-readable size string (e.g., "1.2 GB", "456 MB")
+/// * `allocated_bytes` - Bytes allocated on disk, i.e. what deleting the entry frees
+/// * `shared_bytes` - Hard-linked bytes also reachable from outside this entry
 /// * `is_directory` - Whether this entry represents a directory or a file
+/// * `file_count` - Number of files at or below this entry
+/// * `depth` - Level below the scan root (1 for immediate children)
+/// * `is_partial` - Whether part of the contents could not be read
+/// * `modified` - Newest modification time inside the entry
+/// * `git` - Tracked, untracked or ignored, inside a git work tree
+/// * `mount` - Filesystem type, if a filesystem is mounted on this directory
+///
+/// Entries serialize with the path as a string and `modified` as seconds since the
+/// Unix epoch.
 ///
 /// # Examples
 ///
@@ -54,16 +90,39 @@ use walkdir::WalkDir;
 /// );
 /// assert_eq!(entry.size_human, "1.1 GB");
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct DirectoryEntry {
     /// Full filesystem path to this entry
+    #[serde(serialize_with = "serialize::path")]
     pub path: PathBuf,
     /// Size in bytes (recursive for directories)  
     pub size_bytes: u64,
     /// Human-readable size string (e.g., "1.2 GB")
     pub size_human: String,
+    /// Bytes allocated on disk (recursive for directories)
+    pub allocated_bytes: u64,
+    /// Apparent bytes of hard-linked data that is also linked from outside this entry;
+    /// deleting this entry alone does not reclaim them
+    pub shared_bytes: u64,
+    /// Allocated bytes of the same shared hard-linked data
+    pub shared_allocated_bytes: u64,
     /// True if this entry is a directory, false if it's a file
     pub is_directory: bool,
+    /// Number of files at or below this entry (1 for a file)
+    pub file_count: u64,
+    /// Level below the scan root (1 for immediate children)
+    pub depth: usize,
+    /// True if some contents could not be read, so `size_bytes` is a lower bound
+    pub is_partial: bool,
+    /// Most recent modification time of the entry or anything inside it
+    #[serde(serialize_with = "serialize::optional_unix_secs")]
+    pub modified: Option<SystemTime>,
+    /// Git status and the tracked and ignored bytes inside, if scanned in a work tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitInfo>,
+    /// Type of the filesystem mounted here, if this directory is a mount point
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mount: Option<String>,
 }
 
 impl DirectoryEntry {
@@ -78,6 +137,7 @@ impl DirectoryEntry {
     /// # Returns
     ///
     /// A new `DirectoryEntry` with automatically formatted human-readable size.
+    /// The allocated size starts out equal to the apparent size.
     ///
     /// # Examples
     ///
@@ -95,9 +155,48 @@ impl DirectoryEntry {
             path,
             size_bytes,
             size_human,
+            allocated_bytes: size_bytes,
+            shared_bytes: 0,
+            shared_allocated_bytes: 0,
             is_directory,
+            file_count: u64::from(!is_directory),
+            depth: 1,
+            is_partial: false,
+            modified: None,
+            git: None,
+            mount: None,
         }
     }
+
+    /// Allocated bytes that deleting this entry on its own actually gives back
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.allocated_bytes
+            .saturating_sub(self.shared_allocated_bytes)
+    }
+
+    /// Size of this entry as selected by `mode`
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Which size drives sorting and size filtering.
+///
+/// The apparent size is the file length as reported by `ls`. The allocated size is
+/// the space the filesystem has actually reserved, which is what deleting an entry
+/// gives back; it is smaller for sparse files and VM images, and for files on
+/// compressing filesystems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeMode {
+    /// File lengths, as reported by `ls -l`
+    #[default]
+    Apparent,
+    /// Blocks allocated on disk, as reported by `du`
+    Allocated,
 }
 
 /// High-performance directory analyzer with async processing capabilities.
@@ -120,7 +219,8 @@ impl DirectoryEntry {
 /// let analyzer = DiskAnalyzer::new(3);
 ///
 /// // Analyze directory asynchronously
-/// let entries = analyzer.analyze_directory("/home/user").await?;
+/// let tree = analyzer.analyze_tree("/home/user").await?;
+/// let entries = tree.child_entries(tree.root());
 ///
 /// // Filter large files only
 /// let large_files = analyzer.filter_entries(&entries, Some(100_000_000));
@@ -128,64 +228,109 @@ impl DirectoryEntry {
 #[derive(Debug)]
 pub struct DiskAnalyzer {
     max_depth: usize,
+    size_mode: SizeMode,
+    cache: Option<ScanCache>,
+    threads: Option<NonZeroUsize>,
+    progress: Option<Arc<ScanProgress>>,
+    filter: Option<Arc<PathFilter>>,
+    git_status: bool,
+    one_file_system: bool,
 }
 
 impl DiskAnalyzer {
     pub fn new(max_depth: usize) -> Self {
-        Self { max_depth }
+        Self {
+            max_depth,
+            size_mode: SizeMode::default(),
+            cache: None,
+            threads: None,
+            progress: None,
+            filter: None,
+            git_status: false,
+            one_file_system: false,
+        }
     }
 
-    /// Calculate size of a single file or directory with depth limiting
-    pub fn calculate_size<P: AsRef<Path>>(&self, path: P) -> Result<u64> {
-        let path = path.as_ref();
+    /// Select which size drives sorting and `filter_entries`
+    pub fn with_size_mode(mut self, size_mode: SizeMode) -> Self {
+        self.size_mode = size_mode;
+        self
+    }
 
-        if !path.exists() {
-            return Ok(0);
-        }
+    /// Walk with `threads` threads instead of one per CPU
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
 
-        if path.is_file() {
-            return Ok(path.metadata()?.len());
-        }
+    /// Count files, bytes, pending directories and errors in `progress` while scanning
+    pub fn with_progress(mut self, progress: Arc<ScanProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 
-        let mut total_size = 0u64;
-
-        for entry in WalkDir::new(path)
-            .follow_links(false)
-            .max_depth(self.max_depth)
-        {
-            match entry {
-                Ok(entry) => {
-                    if entry.file_type().is_file() {
-                        if let Ok(metadata) = entry.metadata() {
-                            total_size = total_size.saturating_add(metadata.len());
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Warning: Cannot access {}: {}",
-                        e.path()
-                            .map(|p| p.display().to_string())
-                            .unwrap_or_else(|| "unknown path".to_string()),
-                        e.io_error()
-                            .map(|io_e| io_e.to_string())
-                            .unwrap_or_else(|| "unknown error".to_string())
-                    );
-                    continue;
-                }
-            }
-        }
+    /// The counters updated while scanning, if any
+    pub fn progress(&self) -> Option<&Arc<ScanProgress>> {
+        self.progress.as_ref()
+    }
+
+    /// Leave out what `filter` excludes; excluded directories are never read
+    pub fn with_filter(mut self, filter: PathFilter) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Tag entries as tracked, untracked or ignored when the scanned directory is
+    /// inside a git work tree
+    pub fn with_git_status(mut self, enabled: bool) -> Self {
+        self.git_status = enabled;
+        self
+    }
 
-        Ok(total_size)
+    /// Do not walk into directories on another filesystem than the scanned
+    /// directory; they are listed empty
+    pub fn with_one_file_system(mut self, enabled: bool) -> Self {
+        self.one_file_system = enabled;
+        self
     }
 
-    /// Analyze directory contents and return sorted entries by size
-    pub async fn analyze_directory<P: AsRef<Path>>(
+    /// Reuse directories that are unchanged since the last scan of the same root,
+    /// and remember this scan's directories in `cache` for the next one
+    pub fn with_cache(mut self, cache: ScanCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Scan `target_path` once and return its directory tree, children sorted by size.
+    ///
+    /// Every subtree is walked completely regardless of the display depth, so
+    /// directory totals are never truncated. Directories are read in parallel on a
+    /// bounded thread pool, one per CPU unless set with [`DiskAnalyzer::with_threads`];
+    /// each top-level child is grafted into the result as soon as it is complete.
+    pub async fn analyze_tree<P: AsRef<Path>>(&self, target_path: P) -> Result<DirTree> {
+        self.scan_tree(target_path.as_ref(), None).await
+    }
+
+    /// Like [`DiskAnalyzer::analyze_tree`], but sends every entry down to the display
+    /// depth through `sender` as soon as its size is final.
+    ///
+    /// Entries arrive one top-level child at a time, in the order their scans
+    /// finish; each child is followed by its own descendants, largest first. Their
+    /// sizes, mount points and git status are the same as in the returned tree. The
+    /// sender is dropped when the scan ends, which closes the channel.
+    pub async fn analyze_tree_streaming<P: AsRef<Path>>(
         &self,
         target_path: P,
-    ) -> Result<Vec<DirectoryEntry>> {
-        let path = target_path.as_ref();
+        sender: UnboundedSender<DirectoryEntry>,
+    ) -> Result<DirTree> {
+        self.scan_tree(target_path.as_ref(), Some(sender)).await
+    }
 
+    async fn scan_tree(
+        &self,
+        path: &Path,
+        sender: Option<UnboundedSender<DirectoryEntry>>,
+    ) -> Result<DirTree> {
         if !path.exists() {
             return Err(anyhow::anyhow!(
                 "Directory '{}' does not exist",
@@ -197,44 +342,135 @@ impl DiskAnalyzer {
             return Err(anyhow::anyhow!("'{}' is not a directory", path.display()));
         }
 
-        let mut entries = Vec::new();
-        let mut tasks = Vec::new();
-
-        // Read directory entries with depth limiting
+        let mut tree = DirTree::new(path.to_path_buf());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads.map_or(0, NonZeroUsize::get))
+            .thread_name(|index| format!("scan-{}", index))
+            .panic_handler(|_| eprintln!("Warning: Failed to process entry"))
+            .build()
+            .context("Cannot start the scanning threads")?;
+
+        let cached = self.cache.as_ref().map(|cache| Arc::new(cache.load(path)));
+        let device = if self.one_file_system {
+            let metadata = fs::metadata(path)?;
+            PlatformUtils::file_id(&metadata).map(|id| id.device)
+        } else {
+            None
+        };
+        let mut listings = DirectoryCache::default();
+        let mount_table = MountTable::load();
+        let repo = if self.git_status {
+            RepoStatus::load(path)
+        } else {
+            None
+        };
+
+        // One pool task per top-level child, whose directories are split further
+        // across the pool; hard links inside a child are resolved right away so its
+        // sizes are final before the whole scan is
+        let (results, mut finished) = mpsc::unbounded_channel();
+        let size_mode = self.size_mode;
         for entry in fs::read_dir(path)? {
             let entry = entry?;
             let entry_path = entry.path();
-            let is_directory = entry_path.is_dir();
-
-            // Spawn async task for size calculation
-            let path_clone = entry_path.clone();
-            let max_depth = if is_directory {
-                self.max_depth.saturating_sub(1)
-            } else {
-                1
-            };
-            let analyzer = DiskAnalyzer::new(max_depth);
-
-            let handle = task::spawn_blocking(move || {
-                let size = analyzer.calculate_size(&path_clone).unwrap_or(0);
-                DirectoryEntry::new(path_clone, size, is_directory)
+            if let Some(filter) = &self.filter {
+                let is_directory = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                if filter.excludes(&entry_path, is_directory) {
+                    let name = PathBuf::from(entry.file_name());
+                    let mut excluded = ExcludedTotals::default();
+                    match entry.metadata() {
+                        Ok(meta) if !is_directory => excluded.add(&CachedEntry::file(name, &meta)),
+                        // Counted without a size
+                        _ => excluded.add(&CachedEntry {
+                            is_directory,
+                            ..CachedEntry::directory(name)
+                        }),
+                    }
+                    tree.record_excluded(&excluded);
+                    continue;
+                }
+            }
+            let cached = cached.clone();
+            let progress = self.progress.clone();
+            let filter = self.filter.clone();
+            let results = results.clone();
+            pool.spawn(move || {
+                let options = WalkOptions {
+                    cache: cached.as_deref(),
+                    progress: progress.as_deref(),
+                    filter: filter.as_deref(),
+                    device,
+                };
+                let (mut subtree, listings) = DirTree::scan_with(&entry_path, options);
+                subtree.resolve_hard_links();
+                subtree.sort_by_size(size_mode);
+                let _ = results.send((subtree, listings));
             });
-
-            tasks.push(handle);
+        }
+        drop(results);
+
+        // Collect results as they finish
+        let root = tree.root();
+        while let Some((subtree, found)) = finished.recv().await {
+            listings.extend(found);
+            let child = tree.graft(root, subtree);
+            // Tagged right away so that streamed entries carry their tags too
+            mounts::tag_subtree(&mut tree, child, &mount_table);
+            if let Some(repo) = &repo {
+                git::tag_subtree(&mut tree, child, repo);
+            }
+            if let Some(sender) = &sender {
+                // A closed channel only means nobody is listening any more
+                let _ = sender.send(tree.entry(child));
+                if self.max_depth > 1 {
+                    for entry in tree.entries_to_depth(child, self.max_depth - 1) {
+                        let _ = sender.send(entry);
+                    }
+                }
+            }
         }
 
-        // Collect results
-        for task in tasks {
-            match task.await {
-                Ok(entry) => entries.push(entry),
-                Err(e) => eprintln!("Warning: Failed to process entry: {}", e),
+        // Only what this scan walked is kept, so deleted directories drop out
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.save(path, &listings) {
+                eprintln!("Warning: Cannot update the scan cache: {:#}", e);
             }
         }
 
+        // Hard links can span top-level children, so resolve them on the whole tree
+        tree.resolve_hard_links();
+
+        // Only the root is left to tag
+        mounts::tag_tree(&mut tree, &mount_table);
+        if let Some(repo) = &repo {
+            git::tag_tree(&mut tree, repo);
+        }
+
         // Sort by size (largest first)
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        tree.sort_by_size(self.size_mode);
+
+        Ok(tree)
+    }
 
-        Ok(entries)
+    /// Load a scan exported by ncdu (or `--format ncdu`) instead of walking the disk,
+    /// children sorted by size. `-` reads the export from standard input.
+    pub fn import_ncdu(&self, export: &Path) -> Result<DirTree> {
+        let tree = if export == Path::new("-") {
+            ncdu::read_ncdu(std::io::stdin().lock())
+        } else {
+            fs::File::open(export)
+                .map_err(anyhow::Error::from)
+                .and_then(|file| ncdu::read_ncdu(std::io::BufReader::new(file)))
+        };
+        let mut tree = tree.with_context(|| format!("Cannot import '{}'", export.display()))?;
+        tree.sort_by_size(self.size_mode);
+        Ok(tree)
+    }
+
+    /// Entries of `tree` down to the analyzer's display depth, each directory
+    /// followed by its own children
+    pub fn visible_entries(&self, tree: &DirTree) -> Vec<DirectoryEntry> {
+        tree.entries_to_depth(tree.root(), self.max_depth.max(1))
     }
 
     /// Get entries that match a specific pattern or filter
@@ -247,7 +483,7 @@ impl DiskAnalyzer {
             .iter()
             .filter(|entry| {
                 if let Some(min) = min_size {
-                    entry.size_bytes >= min
+                    entry.size(self.size_mode) >= min
                 } else {
                     true
                 }
@@ -257,6 +493,30 @@ impl DiskAnalyzer {
     }
 }
 
+/// Drops every entry that lies inside another directory in the same list.
+///
+/// Views deeper than one level list a directory together with its children;
+/// totals and deletions must only count the outermost entry once.
+pub fn without_nested(entries: &[DirectoryEntry]) -> Vec<DirectoryEntry> {
+    let directories: HashSet<&Path> = entries
+        .iter()
+        .filter(|entry| entry.is_directory)
+        .map(|entry| entry.path.as_path())
+        .collect();
+
+    entries
+        .iter()
+        .filter(|entry| {
+            !entry
+                .path
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| directories.contains(ancestor))
+        })
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,20 +551,23 @@ mod tests {
     async fn test_calculate_file_size() {
         let temp_dir = create_test_structure().unwrap();
         let file_path = temp_dir.path().join("large_file.txt");
-        let analyzer = DiskAnalyzer::new(3);
 
-        let size = analyzer.calculate_size(&file_path).unwrap();
-        assert_eq!(size, 1000);
+        let tree = DirTree::scan(&file_path);
+        assert_eq!(tree.node(tree.root()).size_bytes, 1000);
     }
 
     #[test]
     async fn test_calculate_directory_size() {
         let temp_dir = create_test_structure().unwrap();
-        let subdir_path = temp_dir.path().join("subdir");
         let analyzer = DiskAnalyzer::new(3);
 
-        let size = analyzer.calculate_size(&subdir_path).unwrap();
-        assert_eq!(size, 500);
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+        let subdir = tree
+            .child_entries(tree.root())
+            .into_iter()
+            .find(|e| e.path.ends_with("subdir"))
+            .unwrap();
+        assert_eq!(subdir.size_bytes, 500);
     }
 
     #[test]
@@ -312,7 +575,8 @@ mod tests {
         let temp_dir = create_test_structure().unwrap();
         let analyzer = DiskAnalyzer::new(1);
 
-        let entries = analyzer.analyze_directory(temp_dir.path()).await.unwrap();
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+        let entries = tree.child_entries(tree.root());
 
         // Should have 4 entries: 2 files + 2 directories
         assert_eq!(entries.len(), 4);
@@ -332,6 +596,40 @@ mod tests {
         assert!(names.contains(&"empty_dir".to_string()));
     }
 
+    #[test]
+    async fn test_streamed_entries_match_final_tree() {
+        let temp_dir = create_test_structure().unwrap();
+        let analyzer = DiskAnalyzer::new(2);
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let tree = analyzer
+            .analyze_tree_streaming(temp_dir.path(), sender)
+            .await
+            .unwrap();
+        let mut streamed = Vec::new();
+        while let Some(entry) = receiver.recv().await {
+            streamed.push(entry);
+        }
+
+        // Each directory arrives right before its own children
+        let subdir = streamed
+            .iter()
+            .position(|e| e.path.ends_with("subdir"))
+            .unwrap();
+        assert!(streamed[subdir + 1]
+            .path
+            .ends_with("subdir/nested_file.txt"));
+
+        // Same entries and sizes as the finished tree, just in arrival order
+        let key = |e: &DirectoryEntry| (e.path.clone(), e.size_bytes, e.depth);
+        let mut expected: Vec<_> = analyzer.visible_entries(&tree).iter().map(key).collect();
+        let mut streamed: Vec<_> = streamed.iter().map(key).collect();
+        expected.sort();
+        streamed.sort();
+        assert_eq!(streamed.len(), 5);
+        assert_eq!(streamed, expected);
+    }
+
     #[test]
     async fn test_directory_entry_creation() {
         let path = PathBuf::from("/test/path");
@@ -341,6 +639,86 @@ mod tests {
         assert_eq!(entry.size_bytes, 1024);
         assert!(entry.is_directory);
         assert_eq!(entry.size_human, "1.02 kB");
+        assert_eq!(entry.file_count, 0);
+        assert_eq!(entry.depth, 1);
+        assert!(!entry.is_partial);
+    }
+
+    #[test]
+    async fn test_sizes_are_recursive_at_depth_one() {
+        let temp_dir = create_test_structure().unwrap();
+        fs::create_dir_all(temp_dir.path().join("subdir/deeper/deepest")).unwrap();
+        File::create(temp_dir.path().join("subdir/deeper/deepest/buried.txt"))
+            .unwrap()
+            .write_all(&[b'b'; 250])
+            .unwrap();
+        let analyzer = DiskAnalyzer::new(1);
+
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+        let entries = analyzer.visible_entries(&tree);
+
+        assert_eq!(entries.len(), 4);
+        let subdir = entries.iter().find(|e| e.path.ends_with("subdir")).unwrap();
+        assert_eq!(subdir.size_bytes, 750);
+        assert!(!subdir.is_partial);
+    }
+
+    #[test]
+    async fn test_visible_entries_follow_display_depth() {
+        let temp_dir = create_test_structure().unwrap();
+        let analyzer = DiskAnalyzer::new(2);
+
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+        let entries = analyzer.visible_entries(&tree);
+
+        assert_eq!(entries.len(), 5);
+        let nested = entries
+            .iter()
+            .position(|e| e.path.ends_with("subdir/nested_file.txt"))
+            .unwrap();
+        assert_eq!(entries[nested].depth, 2);
+        assert!(entries[nested - 1].path.ends_with("subdir"));
+    }
+
+    #[test]
+    async fn test_without_nested() {
+        let entries = vec![
+            DirectoryEntry::new(PathBuf::from("/a"), 1000, true),
+            DirectoryEntry::new(PathBuf::from("/a/b"), 600, true),
+            DirectoryEntry::new(PathBuf::from("/a/b/c.txt"), 600, false),
+            DirectoryEntry::new(PathBuf::from("/ab"), 10, false),
+        ];
+
+        let outer = without_nested(&entries);
+        assert_eq!(outer.len(), 2);
+        assert_eq!(outer[0].path, PathBuf::from("/a"));
+        assert_eq!(outer[1].path, PathBuf::from("/ab"));
+    }
+
+    #[test]
+    async fn test_analyze_tree_keeps_nested_nodes() {
+        let temp_dir = create_test_structure().unwrap();
+        let analyzer = DiskAnalyzer::new(1);
+
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+
+        assert_eq!(tree.node(tree.root()).size_bytes, 1600);
+        assert_eq!(tree.node(tree.root()).file_count, 3);
+
+        let subdir = *tree
+            .children(tree.root())
+            .iter()
+            .find(|&&id| tree.node(id).name == "subdir")
+            .unwrap();
+        assert_eq!(tree.entry(subdir).file_count, 1);
+
+        let nested = tree.child_entries(subdir);
+        assert_eq!(nested.len(), 1);
+        assert_eq!(
+            nested[0].path,
+            temp_dir.path().join("subdir/nested_file.txt")
+        );
+        assert_eq!(nested[0].size_bytes, 500);
     }
 
     #[test]
@@ -359,10 +737,52 @@ mod tests {
         assert_eq!(filtered[1].path, PathBuf::from("medium"));
     }
 
+    #[test]
+    async fn test_filter_entries_by_allocated_size() {
+        let mut sparse = DirectoryEntry::new(PathBuf::from("sparse.img"), 1_000_000, false);
+        sparse.allocated_bytes = 4096;
+        let entries = vec![
+            sparse,
+            DirectoryEntry::new(PathBuf::from("dense.bin"), 50_000, false),
+        ];
+
+        let analyzer = DiskAnalyzer::new(1).with_size_mode(SizeMode::Allocated);
+        let filtered = analyzer.filter_entries(&entries, Some(10_000));
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].path, PathBuf::from("dense.bin"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    async fn test_hard_links_reported_as_shared() {
+        let temp_dir = create_test_structure().unwrap();
+        fs::hard_link(
+            temp_dir.path().join("large_file.txt"),
+            temp_dir.path().join("subdir/linked.txt"),
+        )
+        .unwrap();
+        let analyzer = DiskAnalyzer::new(1);
+
+        let tree = analyzer.analyze_tree(temp_dir.path()).await.unwrap();
+        let entries = analyzer.visible_entries(&tree);
+
+        // Counted once at the root even though two top-level entries link to it
+        assert_eq!(tree.node(tree.root()).size_bytes, 1600);
+
+        let subdir = entries.iter().find(|e| e.path.ends_with("subdir")).unwrap();
+        assert_eq!(subdir.size_bytes, 1500);
+        assert_eq!(subdir.shared_bytes, 1000);
+        assert_eq!(
+            subdir.reclaimable_bytes(),
+            subdir.allocated_bytes - subdir.shared_allocated_bytes
+        );
+    }
+
     #[test]
     async fn test_nonexistent_directory() {
         let analyzer = DiskAnalyzer::new(1);
-        let result = analyzer.analyze_directory("/nonexistent/path").await;
+        let result = analyzer.analyze_tree("/nonexistent/path").await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
@@ -374,7 +794,7 @@ mod tests {
         let file_path = temp_dir.path().join("large_file.txt");
 
         let analyzer = DiskAnalyzer::new(1);
-        let result = analyzer.analyze_directory(&file_path).await;
+        let result = analyzer.analyze_tree(&file_path).await;
 
         assert!(result.is_err());
         assert!(result
@@ -385,8 +805,8 @@ mod tests {
 
     #[test]
     async fn test_calculate_size_nonexistent() {
-        let analyzer = DiskAnalyzer::new(3);
-        let result = analyzer.calculate_size("/nonexistent/file");
-        assert_eq!(result.unwrap(), 0);
+        let tree = DirTree::scan(Path::new("/nonexistent/file"));
+        assert_eq!(tree.node(tree.root()).size_bytes, 0);
+        assert_eq!(tree.errors().count(), 1);
     }
 }
@@ -0,0 +1,377 @@
+//! # Scan Cache
+//!
+//! Remembers the listing of every directory a scan walked, so the next scan of the
+//! same tree can skip directories that have not changed since.
+//! Each scan root gets one JSON file under `$XDG_CACHE_HOME/disk-cleaner/scans`
+//! (`~/.cache/disk-cleaner/scans` by default).
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Invalidation
+//!
+//! A directory's listing is reused when its device, inode, modification time and
+//! change time all match the cached ones; adding, removing or renaming an entry
+//! updates the last two. Subdirectories are still checked one by one, so a change
+//! anywhere below is noticed.
+//!
+//! A file rewritten in place leaves its directory untouched. Files that had been
+//! modified within the cache's age before it was saved (in the day before, for a
+//! cache saved a day ago) are the likeliest to have been rewritten since, so they
+//! are looked at again; the other files of a reused listing are taken from the
+//! cache. Their new size only shows once something else in their directory changes,
+//! or when scanning with `--no-cache`, and the summary warns that sizes may be stale.
+//! Directories that could not be read completely are never cached, and a cache
+//! written by another version of the tool is ignored.
+
+use crate::platform::{DirStamp, FileId, PlatformUtils};
+use crate::serialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Version of the tool that wrote a cache; caches of other versions are ignored
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// One entry of a cached directory listing.
+///
+/// Subdirectories are listed by name only; their own metadata is read again when
+/// they are checked for changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedEntry {
+    #[serde(with = "serialize::path_repr")]
+    pub name: PathBuf,
+    pub is_directory: bool,
+    pub size_bytes: u64,
+    pub allocated_bytes: u64,
+    pub modified: Option<SystemTime>,
+    /// Inode and link count of a file with more than one hard link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hard_link: Option<(FileId, u64)>,
+}
+
+impl CachedEntry {
+    /// Entry for a subdirectory named `name`
+    pub fn directory(name: PathBuf) -> Self {
+        Self {
+            name,
+            is_directory: true,
+            size_bytes: 0,
+            allocated_bytes: 0,
+            modified: None,
+            hard_link: None,
+        }
+    }
+
+    /// Entry for the file (or symbolic link) `name` described by `metadata`
+    pub fn file(name: PathBuf, metadata: &fs::Metadata) -> Self {
+        let link_count = PlatformUtils::link_count(metadata);
+        Self {
+            name,
+            is_directory: false,
+            size_bytes: metadata.len(),
+            allocated_bytes: PlatformUtils::allocated_size(metadata),
+            modified: metadata.modified().ok(),
+            hard_link: PlatformUtils::file_id(metadata)
+                .filter(|_| link_count > 1)
+                .map(|file_id| (file_id, link_count)),
+        }
+    }
+}
+
+/// The listing of one directory as it was when `stamp` was taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedDirectory {
+    /// Path of the directory; relative to the scan root in the cache file
+    #[serde(with = "serialize::path_repr")]
+    pub path: PathBuf,
+    pub stamp: DirStamp,
+    pub entries: Vec<CachedEntry>,
+}
+
+/// Cached listings of one scanned tree, by directory path.
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryCache {
+    directories: HashMap<PathBuf, CachedDirectory>,
+    /// When the listings were saved, if known
+    saved_at: Option<SystemTime>,
+}
+
+impl DirectoryCache {
+    /// The listing of `path`, if it was cached and the directory still has `stamp`
+    pub fn get(&self, path: &Path, stamp: &DirStamp) -> Option<&CachedDirectory> {
+        self.directories
+            .get(path)
+            .filter(|directory| directory.stamp == *stamp)
+    }
+
+    /// The entries of `listing`, with the files that may have been rewritten in
+    /// place since it was cached looked at again.
+    ///
+    /// Those are the files modified within the cache's age before it was saved, or
+    /// every file when that age is unknown.
+    pub fn fresh_entries(&self, listing: &CachedDirectory) -> Vec<CachedEntry> {
+        let since = self.saved_at.and_then(|saved_at| {
+            let age = SystemTime::now().duration_since(saved_at).ok()?;
+            saved_at.checked_sub(age)
+        });
+        listing
+            .entries
+            .iter()
+            .map(|entry| {
+                let recent = match (since, entry.modified) {
+                    (Some(since), Some(modified)) => modified >= since,
+                    _ => true,
+                };
+                if entry.is_directory || !recent {
+                    return entry.clone();
+                }
+                // A file that cannot be looked at keeps its cached size; reading
+                // the directory again reports the error
+                match fs::symlink_metadata(listing.path.join(&entry.name)) {
+                    Ok(metadata) => CachedEntry::file(entry.name.clone(), &metadata),
+                    Err(_) => entry.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Extend<CachedDirectory> for DirectoryCache {
+    fn extend<I: IntoIterator<Item = CachedDirectory>>(&mut self, directories: I) {
+        self.directories.extend(
+            directories
+                .into_iter()
+                .map(|directory| (directory.path.clone(), directory)),
+        );
+    }
+}
+
+/// Contents of one cache file.
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    saved_at: SystemTime,
+    /// Canonical scan root the listings belong to
+    #[serde(with = "serialize::path_repr")]
+    root: PathBuf,
+    directories: Vec<CachedDirectory>,
+}
+
+/// Directory of cached scans.
+#[derive(Debug, Clone)]
+pub struct ScanCache {
+    dir: PathBuf,
+}
+
+impl ScanCache {
+    /// Cache stored in `dir`
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The current user's cache, located through `$XDG_CACHE_HOME` or `$HOME`
+    pub fn for_current_user() -> Option<Self> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(Self::new(cache_home.join("disk-cleaner").join("scans")))
+    }
+
+    /// Listings cached by the last scan of `root`, with paths below `root`.
+    ///
+    /// A missing, unreadable or outdated cache file gives an empty cache: the scan
+    /// then simply reads every directory.
+    pub fn load(&self, root: &Path) -> DirectoryCache {
+        let canonical = canonical_root(root);
+        let file = fs::read(self.file_for(&canonical))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|file| file.version == CACHE_VERSION && file.root == canonical);
+
+        let mut cache = DirectoryCache::default();
+        if let Some(file) = file {
+            cache.saved_at = Some(file.saved_at);
+            cache.extend(
+                file.directories
+                    .into_iter()
+                    .map(|directory| CachedDirectory {
+                        path: root.join(&directory.path),
+                        ..directory
+                    }),
+            );
+        }
+        cache
+    }
+
+    /// Replace the cache of `root` with `cache`, whose paths must lie below `root`
+    pub fn save(&self, root: &Path, cache: &DirectoryCache) -> Result<()> {
+        let canonical = canonical_root(root);
+        let file = CacheFile {
+            version: CACHE_VERSION.to_string(),
+            saved_at: SystemTime::now(),
+            directories: cache
+                .directories
+                .values()
+                .filter_map(|directory| {
+                    let relative = directory.path.strip_prefix(root).ok()?;
+                    Some(CachedDirectory {
+                        path: relative.to_path_buf(),
+                        ..directory.clone()
+                    })
+                })
+                .collect(),
+            root: canonical.clone(),
+        };
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Cannot create cache '{}'", self.dir.display()))?;
+        let path = self.file_for(&canonical);
+        // Write next to the old file and swap it in, so a concurrent scan never
+        // reads half a cache
+        let partial = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&partial, serde_json::to_vec(&file)?)
+            .and_then(|()| fs::rename(&partial, &path))
+            .with_context(|| format!("Cannot write cache '{}'", path.display()))
+    }
+
+    /// Cache file of the canonical scan root `root`
+    fn file_for(&self, root: &Path) -> PathBuf {
+        // FNV-1a, which unlike the standard hasher is stable across builds
+        let hash = PlatformUtils::path_bytes(root)
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+}
+
+/// `root` with symbolic links and relative components resolved, so every way of
+/// naming a directory shares one cache file
+fn canonical_root(root: &Path) -> PathBuf {
+    fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn listing(path: &Path) -> CachedDirectory {
+        let metadata = fs::symlink_metadata(path).unwrap();
+        CachedDirectory {
+            path: path.to_path_buf(),
+            stamp: PlatformUtils::dir_stamp(&metadata).unwrap(),
+            entries: vec![CachedEntry::directory("sub".into())],
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_relative_to_root() {
+        let cache_dir = TempDir::new().unwrap();
+        let scanned = TempDir::new().unwrap();
+        fs::create_dir(scanned.path().join("sub")).unwrap();
+        let store = ScanCache::new(cache_dir.path());
+
+        let mut cache = DirectoryCache::default();
+        cache.extend([listing(&scanned.path().join("sub"))]);
+        store.save(scanned.path(), &cache).unwrap();
+
+        // The same directory reached through another path finds the same listings
+        let other_name = scanned.path().join("sub/..");
+        let loaded = store.load(&other_name);
+        assert_eq!(loaded.directories.len(), 1);
+        let stamp = listing(&scanned.path().join("sub")).stamp;
+        let directory = loaded.get(&other_name.join("sub"), &stamp).unwrap();
+        assert_eq!(directory.entries, [CachedEntry::directory("sub".into())]);
+
+        // A changed directory is not served from the cache
+        fs::create_dir(scanned.path().join("sub/new")).unwrap();
+        let stamp = listing(&scanned.path().join("sub")).stamp;
+        assert!(loaded.get(&other_name.join("sub"), &stamp).is_none());
+    }
+
+    #[test]
+    fn test_recently_modified_files_are_looked_at_again() {
+        let cache_dir = TempDir::new().unwrap();
+        let scanned = TempDir::new().unwrap();
+        let store = ScanCache::new(cache_dir.path());
+        let hot = scanned.path().join("hot.log");
+        let cold = scanned.path().join("cold.iso");
+        fs::write(&hot, [b'h'; 10]).unwrap();
+        fs::write(&cold, [b'c'; 10]).unwrap();
+        let day = std::time::Duration::from_secs(86400);
+        let month_ago = SystemTime::now() - 30 * day;
+        fs::File::options()
+            .write(true)
+            .open(&cold)
+            .unwrap()
+            .set_modified(month_ago)
+            .unwrap();
+
+        let entry = |path: &Path| {
+            let name = PathBuf::from(path.file_name().unwrap());
+            CachedEntry::file(name, &fs::symlink_metadata(path).unwrap())
+        };
+        let mut cache = DirectoryCache::default();
+        cache.extend([CachedDirectory {
+            entries: vec![entry(&hot), entry(&cold)],
+            ..listing(scanned.path())
+        }]);
+        store.save(scanned.path(), &cache).unwrap();
+
+        // Saved a day ago, so files modified in the day before that are read again
+        let file = store.file_for(&canonical_root(scanned.path()));
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        json["saved_at"] = serde_json::to_value(SystemTime::now() - day).unwrap();
+        fs::write(&file, json.to_string()).unwrap();
+
+        fs::write(&hot, [b'h'; 300]).unwrap();
+        fs::write(&cold, [b'c'; 300]).unwrap();
+        let loaded = store.load(scanned.path());
+        let stamp = listing(scanned.path()).stamp;
+        let directory = loaded.get(scanned.path(), &stamp).unwrap();
+        let sizes: Vec<u64> = loaded
+            .fresh_entries(directory)
+            .iter()
+            .map(|entry| entry.size_bytes)
+            .collect();
+        assert_eq!(sizes, [300, 10]);
+
+        // Without knowing the cache's age, every file is
+        let sizes: Vec<u64> = cache
+            .fresh_entries(directory)
+            .iter()
+            .map(|entry| entry.size_bytes)
+            .collect();
+        assert_eq!(sizes, [300, 300]);
+    }
+
+    #[test]
+    fn test_other_versions_are_ignored() {
+        let cache_dir = TempDir::new().unwrap();
+        let scanned = TempDir::new().unwrap();
+        let store = ScanCache::new(cache_dir.path());
+
+        let mut cache = DirectoryCache::default();
+        cache.extend([listing(scanned.path())]);
+        store.save(scanned.path(), &cache).unwrap();
+        assert_eq!(store.load(scanned.path()).directories.len(), 1);
+
+        let file = store.file_for(&canonical_root(scanned.path()));
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        json["version"] = "0.0.1".into();
+        fs::write(&file, json.to_string()).unwrap();
+        assert_eq!(store.load(scanned.path()).directories.len(), 0);
+
+        fs::write(&file, "not json").unwrap();
+        assert_eq!(store.load(scanned.path()).directories.len(), 0);
+    }
+}
@@ -0,0 +1,111 @@
+//! # Error Types
+//!
+//! Typed errors for filesystem operations that callers need to tell apart,
+//! rather than only display. Everything else keeps using `anyhow`.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+
+use crate::access::{Denial, DenyReason};
+use crate::protected::Protection;
+use crate::report::EntryKind;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Why a file or directory could not be deleted.
+#[derive(Debug, Error)]
+pub enum DeleteError {
+    /// The path was gone before deletion was attempted
+    #[error("Path '{}' does not exist", path.display())]
+    NotFound { path: PathBuf },
+
+    /// The permission pre-check refused the path
+    #[error("Insufficient permissions to delete '{}': {denial}", path.display())]
+    PermissionDenied { path: PathBuf, denial: Denial },
+
+    /// The operating system rejected the removal
+    #[error("Failed to delete {kind} '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        kind: EntryKind,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The entry is or contains a protected path
+    #[error("Refusing to delete '{}': {protection}", path.display())]
+    Protected {
+        path: PathBuf,
+        protection: Protection,
+    },
+
+    /// The entry is or contains a mount point, and crossing mounts was not allowed
+    #[error("Refusing to delete '{}': it reaches into the filesystem mounted on '{}'", path.display(), mount.display())]
+    CrossesMount { path: PathBuf, mount: PathBuf },
+
+    /// The entry could not be moved into the trash
+    #[error("Failed to move '{}' to the trash: {source}", path.display())]
+    Trash {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl DeleteError {
+    /// The I/O error kind behind this failure
+    pub fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            DeleteError::NotFound { .. } => io::ErrorKind::NotFound,
+            DeleteError::PermissionDenied { .. } | DeleteError::Protected { .. } => {
+                io::ErrorKind::PermissionDenied
+            }
+            DeleteError::CrossesMount { .. } => io::ErrorKind::CrossesDevices,
+            DeleteError::Io { source, .. } | DeleteError::Trash { source, .. } => source.kind(),
+        }
+    }
+
+    /// Why permissions refused the removal, if they did
+    pub fn deny_reason(&self) -> Option<DenyReason> {
+        match self {
+            DeleteError::PermissionDenied { denial, .. } => Some(denial.reason),
+            _ => None,
+        }
+    }
+
+    /// Message suitable for showing next to the failed path
+    pub fn friendly_message(&self) -> String {
+        match self {
+            DeleteError::Io { source, .. } => {
+                crate::platform::PlatformUtils::friendly_error_message(source)
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_kind_and_messages() {
+        let missing = DeleteError::NotFound {
+            path: PathBuf::from("/tmp/gone"),
+        };
+        assert_eq!(missing.io_kind(), io::ErrorKind::NotFound);
+        assert_eq!(missing.to_string(), "Path '/tmp/gone' does not exist");
+
+        let busy = DeleteError::Io {
+            path: PathBuf::from("/mnt/data"),
+            kind: EntryKind::Directory,
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        };
+        assert_eq!(busy.io_kind(), io::ErrorKind::PermissionDenied);
+        assert!(busy
+            .to_string()
+            .starts_with("Failed to delete directory '/mnt/data'"));
+        assert!(busy.friendly_message().contains("Permission denied"));
+    }
+}
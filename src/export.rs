@@ -0,0 +1,453 @@
+//! # Machine-Readable Export
+//!
+//! Writes analysis results in formats meant for scripts rather than people.
+//! Every format is produced from the same scanned tree and filtered entries that
+//! the interactive table shows.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## JSON
+//!
+//! One document holding the scan root, display depth, size mode, whole-scan totals,
+//! the listed entries and every error hit during the walk:
+//!
+//! ```json
+//! {
+//!   "root": "/home/user",
+//!   "depth": 1,
+//!   "size_mode": "apparent",
+//!   "totals": { "apparent_bytes": 5300, "allocated_bytes": 16384, ... },
+//!   "entries": [ { "path": "/home/user/big.iso", "size_bytes": 5000, ... } ],
+//!   "errors": [ "/home/user/private: Permission denied (os error 13)" ]
+//! }
+//! ```
+//!
+//! ## NDJSON
+//!
+//! One JSON object per line, written while the scan is still running. Every entry
+//! is printed as soon as its size is final, tagged `"type": "entry"`, with the same
+//! fields as in JSON, `mount` and `git` included; entries arrive in scan order
+//! rather than sorted. A last `"type": "summary"` line holds the root, depth, size
+//! mode, totals and errors:
+//!
+//! ```json
+//! {"type":"entry","path":"/home/user/big.iso","size_bytes":5000,...}
+//! {"type":"summary","root":"/home/user","depth":1,"totals":{...},"errors":[]}
+//! ```
+//!
+//! ## CSV and TSV
+//!
+//! One header row, then one row per listed entry with the columns `path`, `type`,
+//! `apparent_bytes`, `size_human`, `depth`, `modified` (local time, empty when
+//! unknown) and `file_count`. Paths are written byte for byte, so names that are
+//! not valid UTF-8 survive the round trip.
+//!
+//! CSV follows RFC 4180: fields holding a comma, quote or line break are quoted,
+//! quotes are doubled and rows end in CRLF. TSV fields cannot be quoted, so tabs,
+//! line breaks and backslashes are escaped as `\t`, `\n`, `\r` and `\\`.
+
+use crate::analyzer::{DirectoryEntry, SizeMode};
+use crate::filter::ExcludedTotals;
+use crate::platform::PlatformUtils;
+use crate::report::EntryKind;
+use crate::serialize;
+use crate::tree::DirTree;
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/// Columns of the CSV and TSV exports, in order.
+const DELIMITED_COLUMNS: [&str; 7] = [
+    "path",
+    "type",
+    "apparent_bytes",
+    "size_human",
+    "depth",
+    "modified",
+    "file_count",
+];
+
+/// Output format for analysis results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Interactive table and selection (the default)
+    #[default]
+    Table,
+    /// One JSON document, no interaction
+    Json,
+    /// One JSON object per line, streamed while scanning
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// ncdu's JSON export of the whole scan, for `ncdu -f` or `--import`
+    Ncdu,
+}
+
+/// Totals over the whole scanned tree, independent of depth and filters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanTotals {
+    pub apparent_bytes: u64,
+    pub allocated_bytes: u64,
+    /// Hard-linked bytes also reachable from outside the scan root
+    pub shared_bytes: u64,
+    pub file_count: u64,
+    /// Number of entries listed in the document
+    pub entry_count: usize,
+    /// True if part of the tree could not be read, so the sizes are lower bounds
+    pub partial: bool,
+    /// What `--exclude` and `--include` left out of the sizes above
+    #[serde(skip_serializing_if = "ExcludedTotals::is_empty")]
+    pub excluded: ExcludedTotals,
+}
+
+impl ScanTotals {
+    /// Totals of the whole of `tree`, with `entry_count` entries listed
+    pub fn new(tree: &DirTree, entry_count: usize) -> Self {
+        let root = tree.node(tree.root());
+        Self {
+            apparent_bytes: root.size_bytes,
+            allocated_bytes: root.allocated_bytes,
+            shared_bytes: root.shared_bytes,
+            file_count: root.file_count,
+            entry_count,
+            partial: root.partial,
+            excluded: *tree.excluded(),
+        }
+    }
+}
+
+/// Everything a scan produced, as one serializable document.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanDocument<'a> {
+    #[serde(serialize_with = "serialize::path")]
+    pub root: PathBuf,
+    pub depth: usize,
+    pub size_mode: SizeMode,
+    pub totals: ScanTotals,
+    pub entries: &'a [DirectoryEntry],
+    pub errors: Vec<&'a str>,
+}
+
+impl<'a> ScanDocument<'a> {
+    /// Document for `entries` listed out of `tree`
+    pub fn new(
+        tree: &'a DirTree,
+        entries: &'a [DirectoryEntry],
+        depth: usize,
+        size_mode: SizeMode,
+    ) -> Self {
+        Self {
+            root: tree.path(tree.root()),
+            depth,
+            size_mode,
+            totals: ScanTotals::new(tree, entries.len()),
+            entries,
+            errors: tree.errors().collect(),
+        }
+    }
+}
+
+/// The closing record of an NDJSON stream: everything but the entries.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSummary<'a> {
+    #[serde(serialize_with = "serialize::path")]
+    pub root: PathBuf,
+    pub depth: usize,
+    pub size_mode: SizeMode,
+    pub totals: ScanTotals,
+    pub errors: Vec<&'a str>,
+}
+
+impl<'a> ScanSummary<'a> {
+    /// Summary of `tree` after `entry_count` entries were streamed
+    pub fn new(tree: &'a DirTree, entry_count: usize, depth: usize, size_mode: SizeMode) -> Self {
+        Self {
+            root: tree.path(tree.root()),
+            depth,
+            size_mode,
+            totals: ScanTotals::new(tree, entry_count),
+            errors: tree.errors().collect(),
+        }
+    }
+}
+
+/// One line of NDJSON output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NdjsonRecord<'a> {
+    Entry(&'a DirectoryEntry),
+    Summary(ScanSummary<'a>),
+}
+
+/// Writes `record` as a single line and flushes it, so readers see it immediately.
+pub fn write_ndjson<W: Write>(mut writer: W, record: &NdjsonRecord) -> Result<()> {
+    serde_json::to_writer(&mut writer, record)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `document` as pretty-printed JSON followed by a newline.
+pub fn write_json<W: Write>(mut writer: W, document: &ScanDocument) -> Result<()> {
+    serde_json::to_writer_pretty(&mut writer, document)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes `entries` as CSV with a header row.
+pub fn write_csv<W: Write>(writer: W, entries: &[DirectoryEntry]) -> Result<()> {
+    write_delimited(writer, entries, Dialect::Csv)
+}
+
+/// Writes `entries` as TSV with a header row.
+pub fn write_tsv<W: Write>(writer: W, entries: &[DirectoryEntry]) -> Result<()> {
+    write_delimited(writer, entries, Dialect::Tsv)
+}
+
+/// Field separator and escaping rules of a delimited text format.
+#[derive(Debug, Clone, Copy)]
+enum Dialect {
+    Csv,
+    Tsv,
+}
+
+impl Dialect {
+    fn separator(self) -> u8 {
+        match self {
+            Dialect::Csv => b',',
+            Dialect::Tsv => b'\t',
+        }
+    }
+
+    fn line_end(self) -> &'static [u8] {
+        match self {
+            Dialect::Csv => b"\r\n",
+            Dialect::Tsv => b"\n",
+        }
+    }
+
+    /// Appends `field` to `row`, quoted or escaped as the format requires
+    fn push_field(self, row: &mut Vec<u8>, field: &[u8]) {
+        match self {
+            Dialect::Csv => {
+                if !field
+                    .iter()
+                    .any(|byte| matches!(byte, b',' | b'"' | b'\r' | b'\n'))
+                {
+                    row.extend_from_slice(field);
+                    return;
+                }
+                row.push(b'"');
+                for &byte in field {
+                    if byte == b'"' {
+                        row.push(b'"');
+                    }
+                    row.push(byte);
+                }
+                row.push(b'"');
+            }
+            Dialect::Tsv => {
+                for &byte in field {
+                    match byte {
+                        b'\t' => row.extend_from_slice(b"\\t"),
+                        b'\n' => row.extend_from_slice(b"\\n"),
+                        b'\r' => row.extend_from_slice(b"\\r"),
+                        b'\\' => row.extend_from_slice(b"\\\\"),
+                        _ => row.push(byte),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes one row made of `fields`
+    fn write_row<W: Write>(self, writer: &mut W, fields: &[&[u8]]) -> Result<()> {
+        let mut row = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                row.push(self.separator());
+            }
+            self.push_field(&mut row, field);
+        }
+        row.extend_from_slice(self.line_end());
+        writer.write_all(&row)?;
+        Ok(())
+    }
+}
+
+fn write_delimited<W: Write>(
+    mut writer: W,
+    entries: &[DirectoryEntry],
+    dialect: Dialect,
+) -> Result<()> {
+    let header = DELIMITED_COLUMNS.map(str::as_bytes);
+    dialect.write_row(&mut writer, &header)?;
+
+    for entry in entries {
+        let modified = entry
+            .modified
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| PlatformUtils::local_time(since_epoch.as_secs()))
+            .unwrap_or_default();
+        let kind = EntryKind::from_is_directory(entry.is_directory).to_string();
+        let (size, depth, files) = (
+            entry.size_bytes.to_string(),
+            entry.depth.to_string(),
+            entry.file_count.to_string(),
+        );
+        dialect.write_row(
+            &mut writer,
+            &[
+                &PlatformUtils::path_bytes(&entry.path),
+                kind.as_bytes(),
+                size.as_bytes(),
+                entry.size_human.as_bytes(),
+                depth.as_bytes(),
+                modified.as_bytes(),
+                files.as_bytes(),
+            ],
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn sample_tree() -> DirTree {
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let logs = tree.push_child(tree.root(), "logs".into(), true, 0);
+        let log = tree.push_child(logs, "app.log".into(), false, 4000);
+        tree.node_mut(log).modified = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        tree.push_child(tree.root(), "notes.txt".into(), false, 20);
+        tree.node_mut(logs)
+            .errors
+            .push("/data/logs/secret: Permission denied".into());
+        tree.finalize();
+        tree
+    }
+
+    #[test]
+    fn test_json_document() {
+        let tree = sample_tree();
+        let entries = tree.child_entries(tree.root());
+        let document = ScanDocument::new(&tree, &entries, 1, SizeMode::Apparent);
+
+        let mut output = Vec::new();
+        write_json(&mut output, &document).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(json["root"], "/data");
+        assert_eq!(json["depth"], 1);
+        assert_eq!(json["size_mode"], "apparent");
+        assert_eq!(json["totals"]["apparent_bytes"], 4020);
+        assert_eq!(json["totals"]["file_count"], 2);
+        assert_eq!(json["totals"]["entry_count"], 2);
+        assert_eq!(json["totals"]["partial"], true);
+        assert_eq!(json["errors"][0], "/data/logs/secret: Permission denied");
+
+        let logs = &json["entries"][0];
+        assert_eq!(logs["path"], "/data/logs");
+        assert_eq!(logs["size_bytes"], 4000);
+        assert_eq!(logs["size_human"], "4 kB");
+        assert_eq!(logs["is_directory"], true);
+        assert_eq!(logs["is_partial"], true);
+        assert_eq!(logs["modified"], 1_700_000_000u64);
+        assert_eq!(json["entries"][1]["modified"], serde_json::Value::Null);
+    }
+
+    fn delimited_entries() -> Vec<DirectoryEntry> {
+        let mut report = DirectoryEntry::new(
+            PathBuf::from("/data/q3, \"final\"\nreport.txt"),
+            2000,
+            false,
+        );
+        report.file_count = 1;
+        let mut tabbed = DirectoryEntry::new(PathBuf::from("/data/a\tb\\c"), 0, true);
+        tabbed.depth = 2;
+        vec![report, tabbed]
+    }
+
+    #[test]
+    fn test_csv_quotes_special_fields() {
+        let mut output = Vec::new();
+        write_csv(&mut output, &delimited_entries()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "path,type,apparent_bytes,size_human,depth,modified,file_count\r\n\
+             \"/data/q3, \"\"final\"\"\nreport.txt\",file,2000,2 kB,1,,1\r\n\
+             /data/a\tb\\c,directory,0,0 B,2,,0\r\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_escapes_special_fields() {
+        let mut output = Vec::new();
+        write_tsv(&mut output, &delimited_entries()).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "/data/q3, \"final\"\\nreport.txt\tfile\t2000\t2 kB\t1\t\t1"
+        );
+        assert!(lines[2].starts_with("/data/a\\tb\\\\c\tdirectory\t"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delimited_paths_keep_invalid_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"/data/caf\xe9,old");
+        let entry = DirectoryEntry::new(PathBuf::from(name), 1, false);
+        let mut output = Vec::new();
+        write_csv(&mut output, &[entry]).unwrap();
+
+        let row = output.split(|&byte| byte == b'\n').nth(1).unwrap();
+        assert_eq!(&row[..16], b"\"/data/caf\xe9,old\"");
+    }
+
+    #[test]
+    fn test_ndjson_records() {
+        let tree = sample_tree();
+        let entries = tree.child_entries(tree.root());
+
+        let mut output = Vec::new();
+        for entry in &entries {
+            write_ndjson(&mut output, &NdjsonRecord::Entry(entry)).unwrap();
+        }
+        let summary = ScanSummary::new(&tree, entries.len(), 1, SizeMode::Apparent);
+        write_ndjson(&mut output, &NdjsonRecord::Summary(summary)).unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "entry");
+        assert_eq!(lines[0]["path"], "/data/logs");
+        assert_eq!(lines[0]["size_bytes"], 4000);
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["root"], "/data");
+        assert_eq!(lines[2]["totals"]["apparent_bytes"], 4020);
+        assert_eq!(lines[2]["totals"]["entry_count"], 2);
+        assert_eq!(
+            lines[2]["errors"][0],
+            "/data/logs/secret: Permission denied"
+        );
+    }
+}
@@ -27,23 +27,90 @@
 //! ```rust
 //! let manager = FileManager::new();
 //! let selected = manager.interactive_select(&entries)?;
-//! let valid = manager.validate_entries(&selected);
+//! let (valid, _denied) = FileManager::split_denied(manager.validate_entries(&selected)?);
 //!
 //! if manager.confirm_deletion(&valid)? {
-//!     let (deleted, failed) = manager.delete_entries(&valid)?;
+//!     let report = manager.delete_entries(&valid)?;
+//!     println!("Freed {} bytes", report.bytes_freed());
 //! }
 //! ```
 
-use crate::analyzer::DirectoryEntry;
+use crate::access::{self, Denial, Removal};
+use crate::analyzer::{self, DirectoryEntry, SizeMode};
+use crate::error::DeleteError;
+use crate::git::GitStatus;
+use crate::mounts::{self, MountTable};
 use crate::platform::PlatformUtils;
-use anyhow::Result;
+use crate::protected::ProtectedPaths;
+use crate::report::{DeletionRecord, DeletionReport, EntryKind, FilesystemDelta};
+use crate::trash::Trash;
+use crate::tree::DirTree;
+use anyhow::{bail, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 // Constants for UI formatting
 const TABLE_WIDTH: usize = 60;
 const SIZE_COLUMN_WIDTH: usize = 8;
 const TYPE_COLUMN_WIDTH: usize = 4;
+const INDENT_WIDTH: usize = 2;
+const PARTIAL_MARKER: &str = "⚠️";
+const SHARED_MARKER: &str = "🔗";
+const IGNORED_MARKER: &str = "🧹";
+const MOUNT_MARKER: &str = "💽";
+
+/// Name of an entry as shown in tables: indented by depth and flagged when its
+/// size is only a lower bound because part of it could not be read, or when part
+/// of it is hard-linked from elsewhere and would not be freed by deleting it, and
+/// with its git status inside a work tree and the type of a filesystem mounted on it
+fn display_name(entry: &DirectoryEntry) -> String {
+    let name = entry
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_else(|| entry.path.to_string_lossy());
+    let indent = " ".repeat(entry.depth.saturating_sub(1) * INDENT_WIDTH);
+    let mut display = format!("{}{}", indent, name);
+    if entry.is_partial {
+        display.push_str(&format!(" {} (partial size)", PARTIAL_MARKER));
+    }
+    if entry.shared_bytes > 0 {
+        display.push_str(&format!(
+            " {} ({} shared)",
+            SHARED_MARKER,
+            humansize::format_size(entry.shared_bytes, humansize::DECIMAL)
+        ));
+    }
+    if let Some(fs_type) = &entry.mount {
+        display.push_str(&format!(" {} ({} mount)", MOUNT_MARKER, fs_type));
+    }
+    if let Some(git) = entry.git {
+        if git.status == GitStatus::Ignored {
+            display.push_str(&format!(" {} ignored", IGNORED_MARKER));
+        } else if git.ignored_bytes > 0 {
+            display.push_str(&format!(
+                " ({}, {} {} ignored)",
+                git.status.label(),
+                IGNORED_MARKER,
+                humansize::format_size(git.ignored_bytes, humansize::DECIMAL)
+            ));
+        } else {
+            display.push_str(&format!(" ({})", git.status.label()));
+        }
+    }
+    display
+}
+
+/// How selected entries are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeleteMode {
+    /// Move entries into the freedesktop.org trash so they can be restored
+    #[default]
+    Trash,
+    /// Remove entries for good
+    Permanent,
+}
 
 /// Handles user interaction for file selection and deletion
 /// Safe file operations manager with interactive user interface capabilities.
@@ -76,26 +143,75 @@ const TYPE_COLUMN_WIDTH: usize = 4;
 /// let selected = manager.interactive_select(&all_entries)?;
 ///
 /// // Validate before operation
-/// let valid = manager.validate_entries(&selected);
-/// let unwritable = manager.get_unwritable_entries(&selected);
+/// let (valid, unwritable) = FileManager::split_denied(manager.validate_entries(&selected)?);
 ///
 /// // Get user confirmation
 /// if manager.confirm_deletion(&valid)? {
-///     let (deleted, failed) = manager.delete_entries(&valid)?;
-///     println!("Successfully deleted {} items", deleted.len());
+///     let report = manager.delete_entries(&valid)?;
+///     println!("Successfully deleted {} items", report.deleted().count());
 /// }
 /// ```
 pub struct FileManager {
     theme: ColorfulTheme,
+    delete_mode: DeleteMode,
+    dry_run: bool,
+    cross_mounts: bool,
+    trash: Option<Trash>,
+    protected: ProtectedPaths,
 }
 
 impl FileManager {
+    /// Why there is no trash to move entries to
+    const NO_TRASH: &'static str = if Trash::SUPPORTED {
+        "no trash location: neither XDG_DATA_HOME nor HOME is set"
+    } else {
+        "the trash is not supported on this platform"
+    };
+
     pub fn new() -> Self {
         Self {
             theme: ColorfulTheme::default(),
+            delete_mode: DeleteMode::default(),
+            dry_run: false,
+            cross_mounts: false,
+            trash: Trash::for_current_user(),
+            protected: ProtectedPaths::new(),
         }
     }
 
+    /// Choose between moving entries to the trash and deleting them permanently
+    pub fn with_delete_mode(mut self, mode: DeleteMode) -> Self {
+        self.delete_mode = mode;
+        self
+    }
+
+    /// Only predict what `delete_entries` would do, without touching the disk
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Allow deleting entries that are or contain mount points, which also deletes
+    /// the contents of the mounted filesystems
+    pub fn with_cross_mounts(mut self, allowed: bool) -> Self {
+        self.cross_mounts = allowed;
+        self
+    }
+
+    /// Refuse to remove the paths in `protected` and anything containing them, on
+    /// top of those protected by default
+    pub fn with_protected(mut self, protected: ProtectedPaths) -> Self {
+        self.protected = protected;
+        self
+    }
+
+    /// Use `trash` instead of the current user's trash
+    #[cfg(test)]
+    pub fn with_trash(mut self, trash: Trash) -> Self {
+        self.trash = Some(trash);
+        self
+    }
+
     /// Display entries in a formatted way and allow multi-selection
     pub fn select_entries(&self, entries: &[DirectoryEntry]) -> Result<Vec<DirectoryEntry>> {
         if entries.is_empty() {
@@ -108,16 +224,11 @@ impl FileManager {
             .iter()
             .map(|entry| {
                 let file_type = if entry.is_directory { "DIR " } else { "FILE" };
-                let name = entry
-                    .path
-                    .file_name()
-                    .map(|n| n.to_string_lossy())
-                    .unwrap_or_else(|| entry.path.to_string_lossy());
                 format!(
                     "{:>width_size$} {:>width_type$} {}",
                     entry.size_human,
                     file_type,
-                    name,
+                    display_name(entry),
                     width_size = SIZE_COLUMN_WIDTH,
                     width_type = TYPE_COLUMN_WIDTH
                 )
@@ -148,9 +259,15 @@ impl FileManager {
             return Ok(false);
         }
 
-        println!("\n🚨 WARNING: The following items will be permanently deleted:");
+        match self.delete_mode {
+            DeleteMode::Trash => {
+                println!("\n🗑️  The following items will be moved to the trash:")
+            }
+            DeleteMode::Permanent => {
+                println!("\n🚨 WARNING: The following items will be permanently deleted:")
+            }
+        }
 
-        let mut total_size = 0u64;
         for entry in entries {
             let file_type = if entry.is_directory { "DIR " } else { "FILE" };
             println!(
@@ -161,11 +278,22 @@ impl FileManager {
                 width_size = SIZE_COLUMN_WIDTH,
                 width_type = TYPE_COLUMN_WIDTH
             );
-            total_size += entry.size_bytes;
         }
 
-        let total_human = humansize::format_size(total_size, humansize::DECIMAL);
-        println!("\n💾 Total size to be freed: {}", total_human);
+        // What deletion gives back is the allocated space, not the apparent length,
+        // minus hard-linked data that stays reachable from elsewhere
+        let reclaimable: u64 = analyzer::without_nested(entries)
+            .iter()
+            .map(|e| e.reclaimable_bytes())
+            .sum();
+        let total_human = humansize::format_size(reclaimable, humansize::DECIMAL);
+        match self.delete_mode {
+            DeleteMode::Trash => println!(
+                "\n💾 Total size to be freed once the trash is emptied: {}",
+                total_human
+            ),
+            DeleteMode::Permanent => println!("\n💾 Total size to be freed: {}", total_human),
+        }
 
         let confirmed = Confirm::with_theme(&self.theme)
             .with_prompt("Are you absolutely sure you want to delete these items?")
@@ -176,60 +304,214 @@ impl FileManager {
     }
 
     /// Delete selected files and directories
-    pub fn delete_entries(&self, entries: &[DirectoryEntry]) -> Result<(Vec<String>, Vec<String>)> {
-        let mut deleted = Vec::new();
-        let mut failed = Vec::new();
-
+    ///
+    /// In dry-run mode each entry is measured and checked exactly as it would be
+    /// before removal, and the report predicts the outcome; nothing is removed.
+    ///
+    /// The space each entry gives back is measured from disk immediately before it
+    /// is removed, so the result never relies on sizes from an older scan and does
+    /// not count hard-linked data that stays reachable elsewhere. Free space of every
+    /// affected filesystem is sampled before the first and after the last deletion.
+    pub fn delete_entries(&self, entries: &[DirectoryEntry]) -> Result<DeletionReport> {
+        let run_started = Instant::now();
+        // Free space cannot change in a dry run, so there is nothing to compare
+        let probes = if self.dry_run {
+            Vec::new()
+        } else {
+            Self::filesystem_probes(entries)
+        };
+        let free_before: Vec<Option<u64>> = probes.iter().map(PlatformUtils::free_space).collect();
+
+        let mounts = MountTable::load();
+        let mut records = Vec::with_capacity(entries.len());
         for (i, entry) in entries.iter().enumerate() {
             print!(
-                "Deleting {}/{}: {}... ",
+                "{} {}/{}: {}... ",
+                if self.dry_run { "Checking" } else { "Deleting" },
                 i + 1,
                 entries.len(),
                 entry.path.display()
             );
 
-            match self.delete_single_entry(&entry.path, entry.is_directory) {
-                Ok(()) => {
+            let started = Instant::now();
+            let kind = EntryKind::from_is_directory(entry.is_directory);
+            let allowed = PlatformUtils::check_protected(&entry.path, &self.protected)
+                .and_then(|()| self.check_mounts(&entry.path, &mounts));
+            // Measuring would walk all of a protected or mounted filesystem too
+            let reclaimable = if allowed.is_ok() {
+                Self::measure_reclaimable(&entry.path)
+            } else {
+                0
+            };
+            let result = allowed.and_then(|()| {
+                if self.dry_run {
+                    PlatformUtils::check_removable(&entry.path, self.removal(), &self.protected)
+                        .map(|()| None)
+                } else {
+                    match self.delete_mode {
+                        DeleteMode::Trash => self.trash_single_entry(&entry.path).map(Some),
+                        DeleteMode::Permanent => self
+                            .delete_single_entry(&entry.path, entry.is_directory)
+                            .map(|()| None),
+                    }
+                }
+            });
+            let elapsed = started.elapsed();
+            let record = match (result, self.delete_mode) {
+                (Ok(destination), DeleteMode::Trash) => {
+                    println!("✅");
+                    DeletionRecord::trashed(
+                        &entry.path,
+                        kind,
+                        destination.as_deref(),
+                        reclaimable,
+                        elapsed,
+                    )
+                }
+                (Ok(_), DeleteMode::Permanent) => {
                     println!("✅");
-                    deleted.push(entry.path.to_string_lossy().to_string());
+                    DeletionRecord::deleted(&entry.path, kind, reclaimable, elapsed)
                 }
-                Err(e) => {
-                    // Use friendly error message for better user experience
-                    let friendly_msg = if let Some(io_error) = e.downcast_ref::<std::io::Error>() {
-                        PlatformUtils::friendly_error_message(io_error)
-                    } else {
-                        e.to_string()
-                    };
-                    println!("❌ ({})", friendly_msg);
-                    failed.push(format!("{} ({})", entry.path.display(), friendly_msg));
+                (Err(e), _) => {
+                    println!("❌ ({})", e.friendly_message());
+                    DeletionRecord::failed(&entry.path, kind, &e, elapsed)
                 }
-            }
+            };
+            records.push(record);
         }
 
-        Ok((deleted, failed))
+        let filesystems = probes
+            .into_iter()
+            .zip(free_before)
+            .filter_map(|(probe, before)| {
+                let after = PlatformUtils::free_space(&probe)?;
+                Some(FilesystemDelta {
+                    probe,
+                    free_before: before?,
+                    free_after: after,
+                })
+            })
+            .collect();
+
+        Ok(DeletionReport {
+            entries: records,
+            filesystems,
+            duration: run_started.elapsed(),
+            dry_run: self.dry_run,
+        })
+    }
+
+    /// Refuses `path` if it is or contains a mount point in `mounts`, unless
+    /// crossing mounts is allowed
+    fn check_mounts(&self, path: &Path, mounts: &MountTable) -> Result<(), DeleteError> {
+        if self.cross_mounts {
+            return Ok(());
+        }
+        match mounts::mount_within(path, mounts) {
+            Some(mount) => Err(DeleteError::CrossesMount {
+                path: path.to_path_buf(),
+                mount,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Allocated bytes that deleting `path` would release right now
+    fn measure_reclaimable(path: &Path) -> u64 {
+        let mut tree = DirTree::scan(path);
+        tree.resolve_hard_links();
+        tree.entry(tree.root()).reclaimable_bytes()
+    }
+
+    /// One surviving directory per filesystem touched by `entries`, used to
+    /// sample free space before and after deletion
+    fn filesystem_probes(entries: &[DirectoryEntry]) -> Vec<PathBuf> {
+        let mut seen = Vec::new();
+        let mut probes = Vec::new();
+        for entry in entries {
+            let Some(parent) = entry.path.parent() else {
+                continue;
+            };
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            let Some(device) = parent
+                .metadata()
+                .ok()
+                .and_then(|meta| PlatformUtils::file_id(&meta))
+                .map(|id| id.device)
+            else {
+                continue;
+            };
+            if !seen.contains(&device) {
+                seen.push(device);
+                probes.push(parent.to_path_buf());
+            }
+        }
+        probes
     }
 
     /// Delete a single file or directory with cross-platform support
-    fn delete_single_entry<P: AsRef<Path>>(&self, path: P, is_directory: bool) -> Result<()> {
-        PlatformUtils::safe_delete(path, is_directory)
+    fn delete_single_entry<P: AsRef<Path>>(
+        &self,
+        path: P,
+        is_directory: bool,
+    ) -> Result<(), DeleteError> {
+        PlatformUtils::safe_delete(path, is_directory, &self.protected)
     }
 
-    /// Validate that all entries still exist and can be deleted before deletion
-    pub fn validate_entries(&self, entries: &[DirectoryEntry]) -> Vec<DirectoryEntry> {
-        entries
-            .iter()
-            .filter(|entry| entry.path.exists() && PlatformUtils::can_delete(&entry.path))
-            .cloned()
-            .collect()
+    /// Move a single file or directory into the trash, returning where it went
+    fn trash_single_entry(&self, path: &Path) -> Result<PathBuf, DeleteError> {
+        let trash = self.trash.as_ref().ok_or_else(|| DeleteError::Trash {
+            path: path.to_path_buf(),
+            source: std::io::Error::other(Self::NO_TRASH),
+        })?;
+        PlatformUtils::safe_trash(path, trash, &self.protected).map(|trashed| trashed.files_path)
+    }
+
+    /// How entries are removed in the current delete mode
+    fn removal(&self) -> Removal {
+        match self.delete_mode {
+            DeleteMode::Trash => Removal::Move,
+            DeleteMode::Permanent => Removal::Delete,
+        }
     }
 
-    /// Get entries that exist but cannot be deleted (for warning the user)
-    pub fn get_unwritable_entries(&self, entries: &[DirectoryEntry]) -> Vec<DirectoryEntry> {
-        entries
+    /// Validate that entries still exist and check once whether each can be removed
+    ///
+    /// Returns the entries that exist, each with why it cannot be removed, if it
+    /// cannot. Fails as a whole if entries are to be moved to the trash but there
+    /// is none.
+    pub fn validate_entries(
+        &self,
+        entries: &[DirectoryEntry],
+    ) -> Result<Vec<(DirectoryEntry, Option<Denial>)>> {
+        if self.delete_mode == DeleteMode::Trash && self.trash.is_none() {
+            bail!("{}; use --permanent to delete for good", Self::NO_TRASH);
+        }
+        Ok(entries
             .iter()
-            .filter(|entry| entry.path.exists() && !PlatformUtils::can_delete(&entry.path))
-            .cloned()
-            .collect()
+            .filter(|entry| entry.path.exists())
+            .map(|entry| (entry.clone(), access::denial(&entry.path, self.removal())))
+            .collect())
+    }
+
+    /// Splits validated entries into those that can be removed and those that
+    /// cannot, with the reason (for warning the user)
+    pub fn split_denied(
+        checked: Vec<(DirectoryEntry, Option<Denial>)>,
+    ) -> (Vec<DirectoryEntry>, Vec<(DirectoryEntry, Denial)>) {
+        let mut valid = Vec::with_capacity(checked.len());
+        let mut denied = Vec::new();
+        for (entry, denial) in checked {
+            match denial {
+                Some(denial) => denied.push((entry, denial)),
+                None => valid.push(entry),
+            }
+        }
+        (valid, denied)
     }
 
     /// Display summary of files in a table format
@@ -242,8 +524,9 @@ impl FileManager {
         println!("\n📊 Directory Contents (sorted by size):");
         println!("{:-<width$}", "", width = TABLE_WIDTH);
         println!(
-            "{:>width_size$} {:>width_type$} NAME",
+            "{:>width_size$} {:>width_size$} {:>width_type$} NAME",
             "SIZE",
+            "ON DISK",
             "TYPE",
             width_size = SIZE_COLUMN_WIDTH,
             width_type = TYPE_COLUMN_WIDTH
@@ -252,31 +535,76 @@ impl FileManager {
 
         for entry in entries {
             let file_type = if entry.is_directory { "DIR " } else { "FILE" };
-            let name = entry
-                .path
-                .file_name()
-                .map(|n| n.to_string_lossy())
-                .unwrap_or_else(|| entry.path.to_string_lossy());
             println!(
-                "{:>width_size$} {:>width_type$} {}",
+                "{:>width_size$} {:>width_size$} {:>width_type$} {}",
                 entry.size_human,
+                humansize::format_size(entry.allocated_bytes, humansize::DECIMAL),
                 file_type,
-                name,
+                display_name(entry),
                 width_size = SIZE_COLUMN_WIDTH,
                 width_type = TYPE_COLUMN_WIDTH
             );
         }
 
-        let total_size: u64 = entries.iter().map(|e| e.size_bytes).sum();
-        let total_human = humansize::format_size(total_size, humansize::DECIMAL);
+        let total_human = humansize::format_size(
+            Self::total_size(entries, SizeMode::Apparent),
+            humansize::DECIMAL,
+        );
+        let allocated_human = humansize::format_size(
+            Self::total_size(entries, SizeMode::Allocated),
+            humansize::DECIMAL,
+        );
         println!("{:-<width$}", "", width = TABLE_WIDTH);
         println!(
-            "{:>width_size$} {:>width_type$} TOTAL",
+            "{:>width_size$} {:>width_size$} {:>width_type$} TOTAL",
             total_human,
+            allocated_human,
             "",
             width_size = SIZE_COLUMN_WIDTH,
             width_type = TYPE_COLUMN_WIDTH
         );
+
+        let partial = entries.iter().filter(|e| e.is_partial).count();
+        if partial > 0 {
+            println!(
+                "\n{}  {} entries have partial sizes: some of their contents could not be read.",
+                PARTIAL_MARKER, partial
+            );
+        }
+
+        let shared = entries.iter().filter(|e| e.shared_bytes > 0).count();
+        if shared > 0 {
+            println!(
+                "\n{}  {} entries contain hard-linked data that is also linked elsewhere; \
+                 deleting them alone will not free the shared bytes.",
+                SHARED_MARKER, shared
+            );
+        }
+
+        let tagged: Vec<_> = analyzer::without_nested(entries)
+            .into_iter()
+            .filter_map(|e| e.git.map(|git| (e.size_bytes, git)))
+            .collect();
+        if !tagged.is_empty() {
+            let total: u64 = tagged.iter().map(|(size, _)| size).sum();
+            let tracked: u64 = tagged.iter().map(|(_, git)| git.tracked_bytes).sum();
+            let ignored: u64 = tagged.iter().map(|(_, git)| git.ignored_bytes).sum();
+            println!(
+                "\n{}  Git: {} ignored (safe to regenerate), {} tracked, {} untracked.",
+                IGNORED_MARKER,
+                humansize::format_size(ignored, humansize::DECIMAL),
+                humansize::format_size(tracked, humansize::DECIMAL),
+                humansize::format_size(total.saturating_sub(tracked + ignored), humansize::DECIMAL)
+            );
+        }
+    }
+
+    /// Combined size of `entries`, counting nested entries only once
+    fn total_size(entries: &[DirectoryEntry], mode: SizeMode) -> u64 {
+        analyzer::without_nested(entries)
+            .iter()
+            .map(|e| e.size(mode))
+            .sum()
     }
 }
 
@@ -289,6 +617,7 @@ impl Default for FileManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::DeletionOutcome;
     use std::fs;
     use std::fs::File;
     use std::io::Write;
@@ -369,7 +698,11 @@ mod tests {
             DirectoryEntry::new(temp_dir.path().join("test_dir"), 300, true),
         ];
 
-        let valid_entries = manager.validate_entries(&entries);
+        let checked = manager.validate_entries(&entries)?;
+        // Each entry is checked once, and these can all be removed
+        assert!(checked.iter().all(|(_, denial)| denial.is_none()));
+        let (valid_entries, denied) = FileManager::split_denied(checked);
+        assert!(denied.is_empty());
 
         // Should only include existing files
         assert_eq!(valid_entries.len(), 2);
@@ -383,20 +716,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_validate_entries_without_trash() -> Result<()> {
+        let temp_dir = create_test_files()?;
+        let entries = vec![DirectoryEntry::new(
+            temp_dir.path().join("test1.txt"),
+            100,
+            false,
+        )];
+        let mut manager = FileManager::new();
+        manager.trash = None;
+
+        let error = manager.validate_entries(&entries).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("use --permanent to delete for good"));
+        let permanent = manager.with_delete_mode(DeleteMode::Permanent);
+        assert_eq!(permanent.validate_entries(&entries)?.len(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_delete_entries() -> Result<()> {
         let temp_dir = create_test_files()?;
-        let manager = FileManager::new();
+        let manager = FileManager::new().with_delete_mode(DeleteMode::Permanent);
 
         let entries = vec![
             DirectoryEntry::new(temp_dir.path().join("test1.txt"), 100, false),
             DirectoryEntry::new(temp_dir.path().join("test2.txt"), 200, false),
         ];
 
-        let (deleted, failed) = manager.delete_entries(&entries)?;
+        let report = manager.delete_entries(&entries)?;
 
-        assert_eq!(deleted.len(), 2);
-        assert_eq!(failed.len(), 0);
+        assert_eq!(report.deleted().count(), 2);
+        assert_eq!(report.failed().count(), 0);
 
         // Verify files are actually deleted
         assert!(!temp_dir.path().join("test1.txt").exists());
@@ -408,24 +761,168 @@ mod tests {
     #[test]
     fn test_delete_entries_with_failures() -> Result<()> {
         let temp_dir = create_test_files()?;
-        let manager = FileManager::new();
+        let manager = FileManager::new().with_delete_mode(DeleteMode::Permanent);
 
         let entries = vec![
             DirectoryEntry::new(temp_dir.path().join("test1.txt"), 100, false),
             DirectoryEntry::new(temp_dir.path().join("nonexistent.txt"), 200, false),
         ];
 
-        let (deleted, failed) = manager.delete_entries(&entries)?;
+        let report = manager.delete_entries(&entries)?;
 
-        assert_eq!(deleted.len(), 1);
-        assert_eq!(failed.len(), 1);
+        assert_eq!(report.deleted().count(), 1);
+        assert_eq!(report.failed().count(), 1);
 
-        assert!(deleted[0].contains("test1.txt"));
-        assert!(failed[0].contains("nonexistent.txt"));
+        let deleted = report.deleted().next().unwrap();
+        let failed = report.failed().next().unwrap();
+        assert!(deleted.path.ends_with("test1.txt"));
+        assert_eq!(deleted.kind, EntryKind::File);
+        assert!(failed.path.ends_with("nonexistent.txt"));
+        assert_eq!(failed.outcome, DeletionOutcome::NotFound);
+        assert_eq!(failed.error_kind, Some(std::io::ErrorKind::NotFound));
+        assert_eq!(failed.bytes, 0);
+        assert!(failed.error.is_some());
 
         Ok(())
     }
 
+    #[test]
+    fn test_delete_entries_measures_reclaimed_bytes() -> Result<()> {
+        let temp_dir = create_test_files()?;
+        let manager = FileManager::new().with_delete_mode(DeleteMode::Permanent);
+
+        // Sizes from an old scan are deliberately wrong; the run re-measures them
+        let entries = vec![DirectoryEntry::new(
+            temp_dir.path().join("test_dir"),
+            999_999,
+            true,
+        )];
+        let expected = FileManager::measure_reclaimable(&temp_dir.path().join("test_dir"));
+
+        let report = manager.delete_entries(&entries)?;
+
+        assert_eq!(report.bytes_freed(), expected);
+        assert_ne!(report.bytes_freed(), 999_999);
+        assert_eq!(report.entries[0].kind, EntryKind::Directory);
+        #[cfg(unix)]
+        {
+            assert_eq!(report.filesystems.len(), 1);
+            assert_eq!(report.filesystems[0].probe, temp_dir.path());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_hard_linked_file_frees_nothing() -> Result<()> {
+        let temp_dir = create_test_files()?;
+        fs::hard_link(
+            temp_dir.path().join("test1.txt"),
+            temp_dir.path().join("test1-link.txt"),
+        )?;
+        let manager = FileManager::new().with_delete_mode(DeleteMode::Permanent);
+
+        let entries = vec![DirectoryEntry::new(
+            temp_dir.path().join("test1.txt"),
+            14,
+            false,
+        )];
+        let report = manager.delete_entries(&entries)?;
+
+        assert_eq!(report.deleted().count(), 1);
+        assert_eq!(report.bytes_freed(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_entries_moves_to_trash_by_default() -> Result<()> {
+        let temp_dir = create_test_files()?;
+        let trash_dir = temp_dir.path().join("Trash");
+        let manager = FileManager::new().with_trash(Trash::new(&trash_dir));
+
+        let entries = vec![
+            DirectoryEntry::new(temp_dir.path().join("test_dir"), 14, true),
+            DirectoryEntry::new(temp_dir.path().join("test1.txt"), 14, false),
+        ];
+        let report = manager.delete_entries(&entries)?;
+
+        assert_eq!(report.deleted().count(), 2);
+        assert!(report
+            .entries
+            .iter()
+            .all(|r| r.outcome == DeletionOutcome::Trashed));
+        assert_eq!(report.bytes_freed(), 0);
+        assert!(report.bytes_trashed() > 0);
+
+        assert!(!temp_dir.path().join("test_dir").exists());
+        assert!(trash_dir.join("files/test_dir/nested.txt").exists());
+        assert!(trash_dir.join("info/test1.txt.trashinfo").exists());
+        assert_eq!(
+            report.entries[1].trashed_to.as_deref(),
+            Some(trash_dir.join("files/test1.txt").as_path())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_predicts_without_deleting() -> Result<()> {
+        let temp_dir = create_test_files()?;
+        let trash_dir = temp_dir.path().join("Trash");
+        let manager = FileManager::new()
+            .with_trash(Trash::new(&trash_dir))
+            .with_dry_run(true);
+
+        let entries = vec![
+            DirectoryEntry::new(temp_dir.path().join("test_dir"), 14, true),
+            DirectoryEntry::new(temp_dir.path().join("nonexistent.txt"), 200, false),
+        ];
+        let expected = FileManager::measure_reclaimable(&temp_dir.path().join("test_dir"));
+        let report = manager.delete_entries(&entries)?;
+
+        assert!(report.dry_run);
+        assert!(report.filesystems.is_empty());
+        assert_eq!(report.entries[0].outcome, DeletionOutcome::Trashed);
+        assert_eq!(report.entries[0].trashed_to, None);
+        assert_eq!(report.bytes_trashed(), expected);
+        assert_eq!(report.entries[1].outcome, DeletionOutcome::NotFound);
+
+        assert!(temp_dir.path().join("test_dir/nested.txt").exists());
+        assert!(!trash_dir.exists());
+
+        let permanent = manager.with_delete_mode(DeleteMode::Permanent);
+        let report = permanent.delete_entries(&entries[..1])?;
+        assert_eq!(report.bytes_freed(), expected);
+        assert!(temp_dir.path().join("test_dir").exists());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_mount_points_are_refused() -> Result<()> {
+        // Any mount point that is not protected for other reasons, such as `/dev/shm`
+        let mounts = MountTable::load();
+        let Some(mount) = mounts.mounts_within(Path::new("/")).find(|mount| {
+            PlatformUtils::check_protected(&mount.path, &ProtectedPaths::new()).is_ok()
+        }) else {
+            return Ok(());
+        };
+        let manager = FileManager::new().with_dry_run(true);
+        let entries = vec![DirectoryEntry::new(mount.path.clone(), 0, true)];
+        let report = manager.delete_entries(&entries)?;
+        assert_eq!(report.entries[0].outcome, DeletionOutcome::Failed);
+        assert_eq!(
+            report.entries[0].error_kind,
+            Some(std::io::ErrorKind::CrossesDevices)
+        );
+        assert!(report.entries[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains(&format!("filesystem mounted on '{}'", mount.path.display())));
+        Ok(())
+    }
+
     #[test]
     fn test_display_summary_empty() {
         let manager = FileManager::new();
@@ -446,4 +943,48 @@ mod tests {
         // Should not panic
         manager.display_summary(&entries);
     }
+
+    #[test]
+    fn test_total_size_counts_nested_entries_once() {
+        let mut child = DirectoryEntry::new(PathBuf::from("/data/logs/app.log"), 400, false);
+        child.depth = 2;
+        let entries = vec![
+            DirectoryEntry::new(PathBuf::from("/data/logs"), 500, true),
+            child,
+            DirectoryEntry::new(PathBuf::from("/data/notes.txt"), 20, false),
+        ];
+
+        assert_eq!(FileManager::total_size(&entries, SizeMode::Apparent), 520);
+    }
+
+    #[test]
+    fn test_total_size_by_mode() {
+        let mut sparse = DirectoryEntry::new(PathBuf::from("/vm/disk.img"), 10_000_000, false);
+        sparse.allocated_bytes = 8192;
+        let entries = vec![
+            sparse,
+            DirectoryEntry::new(PathBuf::from("/vm/log"), 100, false),
+        ];
+
+        assert_eq!(
+            FileManager::total_size(&entries, SizeMode::Apparent),
+            10_000_100
+        );
+        assert_eq!(FileManager::total_size(&entries, SizeMode::Allocated), 8292);
+    }
+
+    #[test]
+    fn test_display_name_indents_and_flags_partial() {
+        let mut entry = DirectoryEntry::new(PathBuf::from("/data/logs/old"), 500, true);
+        entry.depth = 2;
+        entry.is_partial = true;
+
+        let name = display_name(&entry);
+        assert!(name.starts_with("  old"));
+        assert!(name.contains("partial size"));
+        assert!(!name.contains("shared"));
+
+        entry.shared_bytes = 2000;
+        assert!(display_name(&entry).contains("2 kB shared"));
+    }
 }
@@ -0,0 +1,183 @@
+//! # Path Filters
+//!
+//! `--exclude` and `--include` globs, applied while the tree is walked: an excluded
+//! directory is never read, so pseudo filesystems, `.git` and vendored trees cost
+//! nothing. What was left out is counted separately, so the totals of a filtered
+//! scan never pass for the size of the whole directory.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Matching
+//!
+//! Globs are matched against paths relative to the scan root, like the `paths` of
+//! a rules file: a glob without a `/` matches a name at any depth; `*` never
+//! crosses a `/`, `**` does.
+//!
+//! - An entry matching any `--exclude` glob is left out, with everything below it.
+//! - Once an `--include` glob is given, a file is only counted if it, or one of the
+//!   directories it is in, matches one. Directories are still walked, since files
+//!   inside them may match.
+//!
+//! Excludes win over includes.
+
+use crate::cache::CachedEntry;
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Compiled `--exclude` and `--include` globs for one scan root.
+#[derive(Debug, Clone)]
+pub struct PathFilter {
+    root: PathBuf,
+    excludes: GlobSet,
+    includes: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Filter for scans of `root`. Returns `None` if there are no globs at all.
+    pub fn new(root: &Path, excludes: &[String], includes: &[String]) -> Result<Option<Self>> {
+        if excludes.is_empty() && includes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            root: root.to_path_buf(),
+            excludes: compile(excludes, "--exclude")?,
+            includes: if includes.is_empty() {
+                None
+            } else {
+                Some(compile(includes, "--include")?)
+            },
+        }))
+    }
+
+    /// Whether the entry at `path` is left out of the scan
+    pub fn excludes(&self, path: &Path, is_directory: bool) -> bool {
+        // Only entries below the root are filtered, never the root itself
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => return false,
+        };
+        if self.excludes.is_match(relative) {
+            return true;
+        }
+        match &self.includes {
+            Some(includes) if !is_directory => !relative
+                .ancestors()
+                .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| includes.is_match(ancestor)),
+            _ => false,
+        }
+    }
+}
+
+/// One glob set out of `patterns`, given with the option `option`
+fn compile(patterns: &[String], option: &str) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let anchored = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob = GlobBuilder::new(&anchored)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid glob '{}' in {}", pattern, option))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// What a filtered scan left out.
+///
+/// Excluded directories are not read, so only the files left out one by one have
+/// a known size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ExcludedTotals {
+    /// Directories skipped with everything in them
+    pub directories: u64,
+    /// Files left out
+    pub files: u64,
+    /// Apparent bytes of those files
+    pub apparent_bytes: u64,
+    /// Allocated bytes of those files
+    pub allocated_bytes: u64,
+}
+
+impl ExcludedTotals {
+    /// True if nothing was left out
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Counts `entry` as left out
+    pub fn add(&mut self, entry: &CachedEntry) {
+        if entry.is_directory {
+            self.directories += 1;
+        } else {
+            self.files += 1;
+            self.apparent_bytes += entry.size_bytes;
+            self.allocated_bytes += entry.allocated_bytes;
+        }
+    }
+
+    /// Adds everything `other` left out
+    pub fn merge(&mut self, other: &ExcludedTotals) {
+        self.directories += other.directories;
+        self.files += other.files;
+        self.apparent_bytes += other.apparent_bytes;
+        self.allocated_bytes += other.allocated_bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(excludes: &[&str], includes: &[&str]) -> PathFilter {
+        let strings = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect::<Vec<_>>()
+        };
+        PathFilter::new(Path::new("/srv"), &strings(excludes), &strings(includes))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_excludes() {
+        let filter = filter(&[".git", "vendor/*", "*.tmp"], &[]);
+        assert!(filter.excludes(Path::new("/srv/.git"), true));
+        assert!(filter.excludes(Path::new("/srv/app/.git"), true));
+        assert!(filter.excludes(Path::new("/srv/vendor/lib"), true));
+        assert!(!filter.excludes(Path::new("/srv/app/vendor/lib"), true));
+        assert!(filter.excludes(Path::new("/srv/app/cache.tmp"), false));
+        assert!(!filter.excludes(Path::new("/srv/app/main.rs"), false));
+        // The root is never filtered
+        assert!(!filter.excludes(Path::new("/srv"), true));
+    }
+
+    #[test]
+    fn test_includes_keep_matching_files_and_their_contents() {
+        let filter = filter(&["node_modules"], &["*.log", "target"]);
+        assert!(!filter.excludes(Path::new("/srv/app/debug.log"), false));
+        assert!(filter.excludes(Path::new("/srv/app/main.rs"), false));
+        assert!(!filter.excludes(Path::new("/srv/app/target/release/app"), false));
+        // Directories are walked to find matching files
+        assert!(!filter.excludes(Path::new("/srv/app/src"), true));
+        // Excludes win
+        assert!(filter.excludes(Path::new("/srv/node_modules"), true));
+    }
+
+    #[test]
+    fn test_no_globs_no_filter() {
+        assert!(PathFilter::new(Path::new("/srv"), &[], &[])
+            .unwrap()
+            .is_none());
+        assert!(PathFilter::new(Path::new("/srv"), &["[".to_string()], &[]).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! # Git Status
+//!
+//! Tags scanned entries as tracked, untracked or ignored when the scan root lies
+//! inside a git work tree. Ignored entries are usually build output and caches
+//! that can be regenerated, which makes them the safest space to reclaim.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Sources
+//!
+//! The paths are asked from `git ls-files`, so ignore rules are evaluated exactly as
+//! git does: `.gitignore` files at every level, `.git/info/exclude` and the global
+//! excludes file (`core.excludesFile`). Without a `git` executable, or outside a
+//! work tree, nothing is tagged.
+//!
+//! ## Directories
+//!
+//! A file is tracked if it is in the index, ignored if it or a directory above it
+//! matches an ignore rule, and untracked otherwise. A directory is tracked if it
+//! holds any tracked file, ignored if it matches an ignore rule or holds nothing
+//! but ignored entries, and untracked otherwise. Every directory also carries how
+//! many of its bytes are tracked and how many are ignored. The repository's own
+//! `.git` directory is never tagged.
+
+use crate::platform::PlatformUtils;
+use crate::tree::{DirTree, NodeId};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// How git sees an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitStatus {
+    /// In the index, or a directory holding something that is
+    Tracked,
+    /// Neither tracked nor ignored
+    Untracked,
+    /// Matched by an ignore rule
+    Ignored,
+}
+
+impl GitStatus {
+    /// Short label for tables
+    pub fn label(self) -> &'static str {
+        match self {
+            GitStatus::Tracked => "tracked",
+            GitStatus::Untracked => "untracked",
+            GitStatus::Ignored => "ignored",
+        }
+    }
+}
+
+/// Git status of one entry, with the split of its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GitInfo {
+    pub status: GitStatus,
+    /// Apparent bytes of tracked files at or below the entry
+    pub tracked_bytes: u64,
+    /// Apparent bytes of ignored files at or below the entry
+    pub ignored_bytes: u64,
+}
+
+/// Tracked and ignored paths below one scan root, relative to it.
+#[derive(Debug, Clone, Default)]
+pub struct RepoStatus {
+    tracked: HashSet<PathBuf>,
+    ignored: HashSet<PathBuf>,
+}
+
+impl RepoStatus {
+    /// Status of the work tree below `root`, or `None` if `root` is not inside a
+    /// git work tree or git cannot be run.
+    pub fn load(root: &Path) -> Option<Self> {
+        let inside = git(root, &["rev-parse", "--is-inside-work-tree"])?;
+        if inside.trim_ascii() != b"true" {
+            return None;
+        }
+        let tracked = git(root, &["ls-files", "-z"])?;
+        // `--directory` lists a wholly ignored directory once, with a trailing `/`
+        let ignored = git(
+            root,
+            &[
+                "ls-files",
+                "-z",
+                "--others",
+                "--ignored",
+                "--exclude-standard",
+                "--directory",
+            ],
+        )?;
+        Some(Self::from_listings(&tracked, &ignored))
+    }
+
+    /// Status from the NUL-separated outputs of `git ls-files`
+    fn from_listings(tracked: &[u8], ignored: &[u8]) -> Self {
+        let paths = |listing: &[u8]| -> HashSet<PathBuf> {
+            listing
+                .split(|&byte| byte == 0)
+                .filter(|path| !path.is_empty())
+                .map(|path| {
+                    let path = path.strip_suffix(b"/").unwrap_or(path);
+                    // The root itself is listed as `./` when it is ignored
+                    let path = if path == b"." { &[][..] } else { path };
+                    PlatformUtils::path_from_bytes(path.to_vec())
+                })
+                .collect()
+        };
+        Self {
+            tracked: paths(tracked),
+            ignored: paths(ignored),
+        }
+    }
+}
+
+/// Top directory of the git work tree containing `dir`, if any
+pub fn work_tree_root(dir: &Path) -> Option<PathBuf> {
+    let output = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let root = output.strip_suffix(b"\n").unwrap_or(&output);
+    (!root.is_empty()).then(|| PlatformUtils::path_from_bytes(root.to_vec()))
+}
+
+/// Standard output of `git args` run in `dir`, if it succeeded
+fn git(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+/// Tags every node of `tree` with its status in `repo`.
+///
+/// Subtrees already tagged with [`tag_subtree`] are kept as they are; only the
+/// nodes above them are worked out.
+pub fn tag_tree(tree: &mut DirTree, repo: &RepoStatus) {
+    tag_subtree(tree, tree.root(), repo);
+}
+
+/// Tags `id` and every node below it with its status in `repo`, so that a subtree
+/// can be tagged as soon as it is grafted into a tree still being scanned.
+pub fn tag_subtree(tree: &mut DirTree, id: NodeId, repo: &RepoStatus) {
+    if id != tree.root() && tree.node(id).name == ".git" {
+        return;
+    }
+    let root = tree.path(tree.root());
+    let relative = tree
+        .path(id)
+        .strip_prefix(&root)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let ignored_above = relative
+        .ancestors()
+        .skip(1)
+        .any(|ancestor| repo.ignored.contains(ancestor));
+
+    // Parents come before their children, each with its path relative to the root
+    // and whether it is ignored
+    let mut order: Vec<(NodeId, PathBuf, bool)> = Vec::new();
+    let mut pending = vec![(id, relative, ignored_above)];
+    while let Some((id, relative, ignored_above)) = pending.pop() {
+        let ignored = ignored_above || repo.ignored.contains(&relative);
+        for &child in tree.children(id) {
+            let node = tree.node(child);
+            if node.name == ".git" || node.git.is_some() {
+                continue;
+            }
+            pending.push((child, relative.join(&tree.node(child).name), ignored));
+        }
+        order.push((id, relative, ignored));
+    }
+
+    for (id, relative, ignored) in order.into_iter().rev() {
+        let node = tree.node(id);
+        let info = if node.is_directory {
+            let children: Vec<GitInfo> = node
+                .children
+                .iter()
+                .filter_map(|&child| tree.node(child).git)
+                .collect();
+            let status = if children.iter().any(|c| c.status == GitStatus::Tracked) {
+                GitStatus::Tracked
+            } else if ignored
+                || (!children.is_empty() && children.iter().all(|c| c.status == GitStatus::Ignored))
+            {
+                GitStatus::Ignored
+            } else {
+                GitStatus::Untracked
+            };
+            GitInfo {
+                status,
+                tracked_bytes: children.iter().map(|c| c.tracked_bytes).sum(),
+                ignored_bytes: children.iter().map(|c| c.ignored_bytes).sum(),
+            }
+        } else {
+            // A tracked file stays tracked even if an ignore rule matches it
+            let status = if repo.tracked.contains(&relative) {
+                GitStatus::Tracked
+            } else if ignored {
+                GitStatus::Ignored
+            } else {
+                GitStatus::Untracked
+            };
+            let (tracked_bytes, ignored_bytes) = match status {
+                GitStatus::Tracked => (node.size_bytes, 0),
+                GitStatus::Untracked => (0, 0),
+                GitStatus::Ignored => (0, node.size_bytes),
+            };
+            GitInfo {
+                status,
+                tracked_bytes,
+                ignored_bytes,
+            }
+        };
+        tree.node_mut(id).git = Some(info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(paths: &[&str]) -> Vec<u8> {
+        paths
+            .iter()
+            .flat_map(|path| [path.as_bytes(), b"\0"].concat())
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_tree() {
+        let mut tree = DirTree::new(PathBuf::from("/repo"));
+        let root = tree.root();
+        let src = tree.push_child(root, "src".into(), true, 0);
+        let main = tree.push_child(src, "main.rs".into(), false, 100);
+        let scratch = tree.push_child(src, "scratch.rs".into(), false, 10);
+        let object = tree.push_child(src, "main.o".into(), false, 1000);
+        let target = tree.push_child(root, "target".into(), true, 0);
+        let debug = tree.push_child(target, "debug".into(), true, 0);
+        tree.push_child(debug, "app".into(), false, 5000);
+        let git = tree.push_child(root, ".git".into(), true, 0);
+        tree.push_child(git, "index".into(), false, 300);
+        tree.finalize();
+
+        let repo = RepoStatus::from_listings(
+            &listing(&["src/main.rs"]),
+            &listing(&["src/main.o", "target/"]),
+        );
+        tag_tree(&mut tree, &repo);
+
+        let status = |id: NodeId| tree.node(id).git.unwrap().status;
+        assert_eq!(status(main), GitStatus::Tracked);
+        assert_eq!(status(scratch), GitStatus::Untracked);
+        assert_eq!(status(object), GitStatus::Ignored);
+        assert_eq!(status(src), GitStatus::Tracked);
+        assert_eq!(status(debug), GitStatus::Ignored);
+        assert_eq!(status(target), GitStatus::Ignored);
+        assert_eq!(tree.node(git).git, None);
+
+        let totals = tree.node(root).git.unwrap();
+        assert_eq!(totals.status, GitStatus::Tracked);
+        assert_eq!((totals.tracked_bytes, totals.ignored_bytes), (100, 6000));
+    }
+
+    #[test]
+    fn test_tag_subtree_before_the_rest() {
+        let mut tree = DirTree::new(PathBuf::from("/repo"));
+        let root = tree.root();
+        let target = tree.push_child(root, "target".into(), true, 0);
+        let debug = tree.push_child(target, "debug".into(), true, 0);
+        let app = tree.push_child(debug, "app".into(), false, 5000);
+        let main = tree.push_child(root, "main.rs".into(), false, 100);
+        tree.finalize();
+
+        let repo = RepoStatus::from_listings(&listing(&["main.rs"]), &listing(&["target/"]));
+        // Ignored because a directory above it is, though that one is not tagged yet
+        tag_subtree(&mut tree, debug, &repo);
+        assert_eq!(tree.node(app).git.unwrap().status, GitStatus::Ignored);
+        assert_eq!(tree.node(debug).git.unwrap().ignored_bytes, 5000);
+        assert_eq!(tree.node(target).git, None);
+
+        tag_tree(&mut tree, &repo);
+        assert_eq!(tree.node(target).git.unwrap().status, GitStatus::Ignored);
+        assert_eq!(tree.node(main).git.unwrap().status, GitStatus::Tracked);
+        let totals = tree.node(root).git.unwrap();
+        assert_eq!((totals.tracked_bytes, totals.ignored_bytes), (100, 5000));
+    }
+
+    #[test]
+    fn test_ignored_root() {
+        let mut tree = DirTree::new(PathBuf::from("/repo/target"));
+        let root = tree.root();
+        let empty = tree.push_child(root, "empty".into(), true, 0);
+        tree.finalize();
+
+        let repo = RepoStatus::from_listings(&[], &listing(&["./"]));
+        tag_tree(&mut tree, &repo);
+        assert_eq!(tree.node(empty).git.unwrap().status, GitStatus::Ignored);
+        assert_eq!(tree.node(root).git.unwrap().status, GitStatus::Ignored);
+    }
+}
@@ -0,0 +1,281 @@
+//! # HTML Report
+//!
+//! Renders a scanned tree as a single self-contained HTML page for sharing with
+//! people who will never run the tool: a zoomable treemap, a sortable table of the
+//! largest entries and a breakdown by file extension. All data is embedded in the
+//! page and no external resources are loaded, so the file works offline.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Size Limits
+//!
+//! The page stays small however large the scan was:
+//!
+//! - The treemap goes [`TREEMAP_MAX_DEPTH`] levels deep, and entries smaller than
+//!   1/[`TREEMAP_MIN_FRACTION`] of the scan are merged into one tile per directory
+//! - The table lists the [`TABLE_MAX_ROWS`] largest entries of the first two levels
+//! - The [`EXTENSION_MAX_ROWS`] largest extensions are listed, the rest summed up
+
+use crate::analyzer::{DirectoryEntry, SizeMode};
+use crate::export::ScanTotals;
+use crate::platform::PlatformUtils;
+use crate::tree::{DirTree, NodeId};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Page template; the report data replaces [`DATA_PLACEHOLDER`]
+const TEMPLATE: &str = include_str!("html_report.html");
+const DATA_PLACEHOLDER: &str = "__REPORT_DATA__";
+
+/// Levels of the tree shown in the treemap
+const TREEMAP_MAX_DEPTH: usize = 8;
+/// Entries below this fraction of the whole scan share one treemap tile
+const TREEMAP_MIN_FRACTION: u64 = 2000;
+/// Rows in the largest-entries table
+const TABLE_MAX_ROWS: usize = 1000;
+/// Rows in the extension breakdown before the remainder is summed up
+const EXTENSION_MAX_ROWS: usize = 40;
+
+/// One tile of the treemap.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct TreemapNode {
+    name: String,
+    size: u64,
+    files: u64,
+    dir: bool,
+    /// Stands for several entries too small to draw on their own
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    other: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreemapNode>,
+}
+
+/// Bytes and files sharing one extension.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ExtensionUsage {
+    extension: String,
+    bytes: u64,
+    files: u64,
+}
+
+/// Everything embedded in the page.
+#[derive(Debug, Serialize)]
+struct ReportData<'a> {
+    root: String,
+    generated: String,
+    size_mode: SizeMode,
+    totals: ScanTotals,
+    tree: TreemapNode,
+    entries: Vec<DirectoryEntry>,
+    extensions: Vec<ExtensionUsage>,
+    errors: Vec<&'a str>,
+}
+
+/// Writes the HTML report for `tree`, sizing everything by `size_mode`.
+pub fn write_html<W: Write>(mut writer: W, tree: &DirTree, size_mode: SizeMode) -> Result<()> {
+    let mut entries = tree.entries_to_depth(tree.root(), 2);
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.size(size_mode)));
+    entries.truncate(TABLE_MAX_ROWS);
+
+    let generated = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| PlatformUtils::local_time(since_epoch.as_secs()))
+        .unwrap_or_default();
+    let root_size = tree.node(tree.root()).size(size_mode);
+
+    let data = ReportData {
+        root: tree.path(tree.root()).to_string_lossy().into_owned(),
+        generated,
+        size_mode,
+        totals: ScanTotals::new(tree, entries.len()),
+        tree: treemap_node(
+            tree,
+            tree.root(),
+            size_mode,
+            root_size / TREEMAP_MIN_FRACTION,
+            0,
+        ),
+        entries,
+        extensions: extension_usage(tree, size_mode),
+        errors: tree.errors().collect(),
+    };
+
+    // `<` only occurs inside JSON strings, where `\u003c` means the same; escaping
+    // it keeps names like `</script>` from ending the data block early
+    let json = serde_json::to_string(&data)?.replace('<', "\\u003c");
+    let page = TEMPLATE.replacen(DATA_PLACEHOLDER, &json, 1);
+    writer.write_all(page.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Treemap tile for `id`, with children down to [`TREEMAP_MAX_DEPTH`] and those
+/// smaller than `min_size` merged into one tile
+fn treemap_node(
+    tree: &DirTree,
+    id: NodeId,
+    size_mode: SizeMode,
+    min_size: u64,
+    depth: usize,
+) -> TreemapNode {
+    let node = tree.node(id);
+    let mut tile = TreemapNode {
+        name: node.name.to_string_lossy().into_owned(),
+        size: node.size(size_mode),
+        files: node.file_count,
+        dir: node.is_directory,
+        other: false,
+        children: Vec::new(),
+    };
+    if depth >= TREEMAP_MAX_DEPTH {
+        return tile;
+    }
+
+    let (mut small_count, mut small_size, mut small_files) = (0, 0u64, 0u64);
+    for &child in tree.children(id) {
+        let child_node = tree.node(child);
+        if child_node.size(size_mode) >= min_size.max(1) {
+            tile.children
+                .push(treemap_node(tree, child, size_mode, min_size, depth + 1));
+        } else {
+            small_count += 1;
+            small_size += child_node.size(size_mode);
+            small_files += child_node.file_count;
+        }
+    }
+    if small_count > 0 {
+        tile.children.push(TreemapNode {
+            name: format!("{} smaller entries", small_count),
+            size: small_size,
+            files: small_files,
+            dir: false,
+            other: true,
+            children: Vec::new(),
+        });
+    }
+    tile
+}
+
+/// Size and file count per lowercase extension, largest first
+fn extension_usage(tree: &DirTree, size_mode: SizeMode) -> Vec<ExtensionUsage> {
+    let mut usage: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut stack = vec![tree.root()];
+    while let Some(id) = stack.pop() {
+        let node = tree.node(id);
+        if node.is_directory {
+            stack.extend(tree.children(id));
+            continue;
+        }
+        let extension = Path::new(&node.name)
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "(none)".to_string());
+        let (bytes, files) = usage.entry(extension).or_default();
+        *bytes += node.size(size_mode);
+        *files += 1;
+    }
+
+    let mut usage: Vec<ExtensionUsage> = usage
+        .into_iter()
+        .map(|(extension, (bytes, files))| ExtensionUsage {
+            extension,
+            bytes,
+            files,
+        })
+        .collect();
+    usage.sort_by(|a, b| {
+        b.bytes
+            .cmp(&a.bytes)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+
+    if usage.len() > EXTENSION_MAX_ROWS {
+        let rest = usage.split_off(EXTENSION_MAX_ROWS - 1);
+        usage.push(ExtensionUsage {
+            extension: format!("({} others)", rest.len()),
+            bytes: rest.iter().map(|ext| ext.bytes).sum(),
+            files: rest.iter().map(|ext| ext.files).sum(),
+        });
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample_tree() -> DirTree {
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let media = tree.push_child(tree.root(), "media".into(), true, 0);
+        tree.push_child(media, "film.MKV".into(), false, 900_000);
+        tree.push_child(media, "clip.mkv".into(), false, 90_000);
+        tree.push_child(media, "thumb.jpg".into(), false, 300);
+        // Paths like `/data/</script>.txt` must not end the embedded data early
+        let angle = tree.push_child(tree.root(), "<".into(), true, 0);
+        tree.push_child(angle, "script>.txt".into(), false, 9_700);
+        tree.push_child(tree.root(), "Makefile".into(), false, 10);
+        tree.finalize();
+        tree
+    }
+
+    #[test]
+    fn test_small_entries_share_a_tile() {
+        let tree = sample_tree();
+        let tile = treemap_node(&tree, tree.root(), SizeMode::Apparent, 1000, 0);
+
+        assert_eq!(tile.name, "/data");
+        assert_eq!(tile.size, 1_000_010);
+        let names: Vec<&str> = tile.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["media", "<", "1 smaller entries"]);
+
+        let media = &tile.children[0];
+        assert_eq!(media.children.len(), 3);
+        assert!(media.children[2].other);
+        assert_eq!(media.children[2].size, 300);
+    }
+
+    #[test]
+    fn test_extension_usage() {
+        let tree = sample_tree();
+        let usage = extension_usage(&tree, SizeMode::Apparent);
+
+        assert_eq!(
+            usage[0],
+            ExtensionUsage {
+                extension: ".mkv".into(),
+                bytes: 990_000,
+                files: 2
+            }
+        );
+        let extensions: Vec<&str> = usage.iter().map(|u| u.extension.as_str()).collect();
+        assert_eq!(extensions, [".mkv", ".txt", ".jpg", "(none)"]);
+    }
+
+    #[test]
+    fn test_page_embeds_escaped_data() {
+        let tree = sample_tree();
+        let mut output = Vec::new();
+        write_html(&mut output, &tree, SizeMode::Apparent).unwrap();
+        let page = String::from_utf8(output).unwrap();
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(!page.contains(DATA_PLACEHOLDER));
+        assert_eq!(page.matches("</script>").count(), 2);
+        assert!(page.contains("\\u003c/script>.txt"));
+        assert!(!page.contains("http://") && !page.contains("https://"));
+
+        let start = page.find("id=\"report-data\">").unwrap() + 17;
+        let end = start + page[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&page[start..end]).unwrap();
+        assert_eq!(data["root"], "/data");
+        assert_eq!(data["totals"]["apparent_bytes"], 1_000_010);
+        assert_eq!(data["entries"][0]["path"], "/data/media");
+        assert_eq!(data["extensions"][0]["extension"], ".mkv");
+    }
+}
@@ -0,0 +1,513 @@
+//! # Cleanup Journal
+//!
+//! Records every cleanup session so that it can be reviewed and undone later.
+//! Each session is one JSON file under `$XDG_STATE_HOME/disk-cleaner/sessions`
+//! (`~/.local/state/disk-cleaner/sessions` by default) listing the original path,
+//! where the entry went, its size and when it was removed.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Restoring
+//!
+//! Entries that were moved to the trash are renamed back to their original path and
+//! their `.trashinfo` file is removed. Nothing is ever overwritten: if something new
+//! now sits at the original path the entry is reported as a conflict and left in
+//! the trash. Permanently deleted entries are listed but cannot be brought back.
+
+use crate::report::{DeletionReport, EntryKind};
+use crate::serialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One removed entry as recorded in the journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Where the entry lived before the cleanup
+    #[serde(with = "serialize::path_repr")]
+    pub original: PathBuf,
+    pub kind: EntryKind,
+    /// Where the entry went; `None` if it was deleted permanently
+    #[serde(with = "serialize::optional_path_repr")]
+    pub destination: Option<PathBuf>,
+    /// Allocated bytes the entry held when it was removed
+    pub bytes: u64,
+    /// Seconds since the Unix epoch when the entry was removed
+    pub removed_at: u64,
+    /// Seconds since the Unix epoch when the entry was put back, if it was
+    #[serde(default)]
+    pub restored_at: Option<u64>,
+}
+
+/// All entries removed by one run of the tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Identifier used to pick the session for `restore`
+    pub id: String,
+    /// Seconds since the Unix epoch when the session was recorded
+    pub started_at: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Session {
+    /// Total bytes the session removed
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.bytes).sum()
+    }
+
+    /// Entries that are still in the trash and could be put back
+    pub fn restorable(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.destination.is_some() && e.restored_at.is_none())
+    }
+}
+
+/// What happened when restoring one entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    /// The entry is back at its original path
+    Restored,
+    /// Something else now exists at the original path; nothing was moved
+    Conflict,
+    /// The trashed copy is gone, probably because the trash was emptied
+    Missing,
+    /// The entry was deleted permanently
+    Permanent,
+    /// The entry was already restored by an earlier run
+    AlreadyRestored,
+    /// The move back failed
+    Failed(String),
+}
+
+/// Result of restoring one journal entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoreResult {
+    pub original: PathBuf,
+    pub outcome: RestoreOutcome,
+}
+
+/// Directory of recorded sessions.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    /// Journal stored in `dir`
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The current user's journal, located through `$XDG_STATE_HOME` or `$HOME`
+    pub fn for_current_user() -> Option<Self> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+            })?;
+        Some(Self::new(state_home.join("disk-cleaner").join("sessions")))
+    }
+
+    /// Record the successful removals of a deletion run as a new session.
+    ///
+    /// Returns `None` without writing anything if nothing was removed.
+    pub fn record(&self, report: &DeletionReport) -> Result<Option<Session>> {
+        if report.dry_run {
+            return Ok(None);
+        }
+        let now = unix_now();
+        let entries: Vec<JournalEntry> = report
+            .deleted()
+            .map(|record| JournalEntry {
+                original: record.path.clone(),
+                kind: record.kind,
+                destination: record.trashed_to.clone(),
+                bytes: record.bytes,
+                removed_at: now,
+                restored_at: None,
+            })
+            .collect();
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let session = Session {
+            id: self.unused_id(now),
+            started_at: now,
+            entries,
+        };
+        self.save(&session)?;
+        Ok(Some(session))
+    }
+
+    /// All recorded sessions, oldest first
+    pub fn sessions(&self) -> Result<Vec<Session>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Cannot read journal '{}'", self.dir.display()))
+            }
+        };
+
+        let mut sessions = Vec::new();
+        for entry in read_dir {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let contents = fs::read_to_string(&path)
+                    .with_context(|| format!("Cannot read session '{}'", path.display()))?;
+                let session: Session = serde_json::from_str(&contents)
+                    .with_context(|| format!("Corrupt session file '{}'", path.display()))?;
+                sessions.push(session);
+            }
+        }
+        sessions.sort_by(|a, b| (a.started_at, &a.id).cmp(&(b.started_at, &b.id)));
+        Ok(sessions)
+    }
+
+    /// The session with identifier `id`
+    pub fn session(&self, id: &str) -> Result<Session> {
+        self.sessions()?
+            .into_iter()
+            .find(|s| s.id == id)
+            .with_context(|| format!("No cleanup session '{}' in the journal", id))
+    }
+
+    /// Put the trashed entries of session `id` back where they were
+    pub fn restore(&self, id: &str) -> Result<Vec<RestoreResult>> {
+        let mut session = self.session(id)?;
+        let now = unix_now();
+
+        let results = session
+            .entries
+            .iter_mut()
+            .map(|entry| {
+                let outcome = restore_entry(entry);
+                if outcome == RestoreOutcome::Restored {
+                    entry.restored_at = Some(now);
+                }
+                RestoreResult {
+                    original: entry.original.clone(),
+                    outcome,
+                }
+            })
+            .collect();
+
+        self.save(&session)?;
+        Ok(results)
+    }
+
+    fn session_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Identifier based on the session time, made unique if several runs share a second
+    fn unused_id(&self, now: u64) -> String {
+        let base = crate::platform::PlatformUtils::local_time(now).replace([':', 'T'], "-");
+        let mut id = base.clone();
+        let mut n = 2;
+        while self.session_path(&id).exists() {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+        id
+    }
+
+    fn save(&self, session: &Session) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Cannot create journal '{}'", self.dir.display()))?;
+        let path = self.session_path(&session.id);
+        let contents = serde_json::to_string_pretty(session)?;
+        fs::write(&path, contents)
+            .with_context(|| format!("Cannot write session '{}'", path.display()))
+    }
+}
+
+/// Move one trashed entry back, never replacing anything at its original path
+fn restore_entry(entry: &JournalEntry) -> RestoreOutcome {
+    if entry.restored_at.is_some() {
+        return RestoreOutcome::AlreadyRestored;
+    }
+    let Some(destination) = &entry.destination else {
+        return RestoreOutcome::Permanent;
+    };
+    if destination.symlink_metadata().is_err() {
+        return RestoreOutcome::Missing;
+    }
+    if entry.original.symlink_metadata().is_ok() {
+        return RestoreOutcome::Conflict;
+    }
+
+    let moved = match entry.original.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+    .and_then(|()| rename_no_replace(destination, &entry.original));
+    match moved {
+        Ok(()) => {}
+        // Something appeared at the original path since it was checked
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return RestoreOutcome::Conflict,
+        Err(e) => return RestoreOutcome::Failed(e.to_string()),
+    }
+
+    if let Some(info) = trash_info_path(destination) {
+        let _ = fs::remove_file(info);
+    }
+    RestoreOutcome::Restored
+}
+
+/// Renames `from` to `to`, failing with `AlreadyExists` rather than replacing
+/// anything that is at `to`, even if it appears while renaming.
+///
+/// Linux does this in one step with `RENAME_NOREPLACE`. Elsewhere, and on
+/// filesystems that do not support it, files are hard-linked into place and then
+/// unlinked; directories cannot be, so for them only an empty directory created
+/// between the check and the rename could still be replaced.
+fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let from_c = CString::new(from.as_os_str().as_bytes())?;
+        let to_c = CString::new(to.as_os_str().as_bytes())?;
+        // SAFETY: both paths are valid NUL-terminated strings
+        let result = unsafe {
+            libc::renameat2(
+                libc::AT_FDCWD,
+                from_c.as_ptr(),
+                libc::AT_FDCWD,
+                to_c.as_ptr(),
+                libc::RENAME_NOREPLACE,
+            )
+        };
+        if result == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        if !matches!(error.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS)) {
+            return Err(error);
+        }
+    }
+
+    if from.symlink_metadata()?.is_dir() {
+        if to.symlink_metadata().is_ok() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        return fs::rename(from, to);
+    }
+    fs::hard_link(from, to)?;
+    fs::remove_file(from)
+}
+
+/// The `.trashinfo` file that describes an entry in a trash's `files/` directory.
+/// Trashes without a `files/` directory, like Finder's, keep no such file.
+fn trash_info_path(files_path: &Path) -> Option<PathBuf> {
+    let name = files_path.file_name()?;
+    let files_dir = files_path.parent()?;
+    if files_dir.file_name()? != "files" {
+        return None;
+    }
+    let trash_dir = files_dir.parent()?;
+    let mut info_name = name.to_os_string();
+    info_name.push(".trashinfo");
+    Some(trash_dir.join("info").join(info_name))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::DeletionRecord;
+    use crate::trash::Trash;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Trash `names` from `base` and record the run in `journal`
+    fn trash_and_record(base: &Path, journal: &Journal, names: &[&str]) -> Session {
+        let trash = Trash::new(base.join("Trash"));
+        let entries = names
+            .iter()
+            .map(|name| {
+                let path = base.join(name);
+                let trashed = trash.move_to_trash(&path).unwrap();
+                DeletionRecord::trashed(
+                    &path,
+                    EntryKind::File,
+                    Some(&trashed.files_path),
+                    5,
+                    Duration::ZERO,
+                )
+            })
+            .collect();
+        let report = DeletionReport {
+            entries,
+            ..Default::default()
+        };
+        journal.record(&report).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_record_and_list_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        assert!(journal.sessions().unwrap().is_empty());
+
+        fs::write(temp_dir.path().join("a.log"), b"aaaaa").unwrap();
+        let session = trash_and_record(temp_dir.path(), &journal, &["a.log"]);
+
+        let sessions = journal.sessions().unwrap();
+        assert_eq!(sessions, vec![session.clone()]);
+        assert_eq!(session.total_bytes(), 5);
+        assert_eq!(session.restorable().count(), 1);
+    }
+
+    #[test]
+    fn test_empty_report_is_not_recorded() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::new(temp_dir.path().join("journal"));
+
+        assert!(journal
+            .record(&DeletionReport::default())
+            .unwrap()
+            .is_none());
+        assert!(!temp_dir.path().join("journal").exists());
+    }
+
+    #[test]
+    fn test_restore_puts_entries_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        let original = temp_dir.path().join("a.log");
+        fs::write(&original, b"aaaaa").unwrap();
+        let session = trash_and_record(temp_dir.path(), &journal, &["a.log"]);
+        assert!(!original.exists());
+
+        let results = journal.restore(&session.id).unwrap();
+
+        assert_eq!(results[0].outcome, RestoreOutcome::Restored);
+        assert_eq!(fs::read(&original).unwrap(), b"aaaaa");
+        assert!(!temp_dir.path().join("Trash/info/a.log.trashinfo").exists());
+
+        // Restoring twice is harmless
+        let again = journal.restore(&session.id).unwrap();
+        assert_eq!(again[0].outcome, RestoreOutcome::AlreadyRestored);
+        assert_eq!(
+            journal.session(&session.id).unwrap().restorable().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_restore_detects_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        let original = temp_dir.path().join("a.log");
+        fs::write(&original, b"old").unwrap();
+        let session = trash_and_record(temp_dir.path(), &journal, &["a.log"]);
+        fs::write(&original, b"new").unwrap();
+
+        let results = journal.restore(&session.id).unwrap();
+
+        assert_eq!(results[0].outcome, RestoreOutcome::Conflict);
+        assert_eq!(fs::read(&original).unwrap(), b"new");
+        assert_eq!(
+            journal.session(&session.id).unwrap().restorable().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rename_never_replaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("from");
+        let to = temp_dir.path().join("to");
+        fs::write(&from, b"old").unwrap();
+        fs::write(&to, b"new").unwrap();
+
+        let error = rename_no_replace(&from, &to).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&from).unwrap(), b"old");
+        assert_eq!(fs::read(&to).unwrap(), b"new");
+
+        fs::remove_file(&to).unwrap();
+        rename_no_replace(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read(&to).unwrap(), b"old");
+
+        // Directories are moved as a whole
+        fs::create_dir_all(from.join("inner")).unwrap();
+        let moved = temp_dir.path().join("moved");
+        rename_no_replace(&from, &moved).unwrap();
+        assert!(moved.join("inner").is_dir());
+        assert_eq!(
+            rename_no_replace(&moved, &to).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+    }
+
+    #[test]
+    fn test_restore_reports_missing_and_permanent_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        fs::write(temp_dir.path().join("a.log"), b"aaaaa").unwrap();
+        let session = trash_and_record(temp_dir.path(), &journal, &["a.log"]);
+        fs::remove_dir_all(temp_dir.path().join("Trash")).unwrap();
+
+        let permanent = DeletionReport {
+            entries: vec![DeletionRecord::deleted(
+                &temp_dir.path().join("b.log"),
+                EntryKind::File,
+                5,
+                Duration::ZERO,
+            )],
+            ..Default::default()
+        };
+        let permanent = journal.record(&permanent).unwrap().unwrap();
+        assert_ne!(permanent.id, session.id);
+
+        assert_eq!(
+            journal.restore(&session.id).unwrap()[0].outcome,
+            RestoreOutcome::Missing
+        );
+        assert_eq!(
+            journal.restore(&permanent.id).unwrap()[0].outcome,
+            RestoreOutcome::Permanent
+        );
+        assert!(journal.restore("no-such-session").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_paths_round_trip() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new("/data").join(OsStr::from_bytes(b"caf\xe9.txt"));
+        let entry = JournalEntry {
+            original: path.clone(),
+            kind: EntryKind::File,
+            destination: Some(path.clone()),
+            bytes: 1,
+            removed_at: 0,
+            restored_at: None,
+        };
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let parsed: JournalEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, entry);
+    }
+}
@@ -0,0 +1,25 @@
+//! # Disk Cleaner Library
+//!
+//! The scanning and deletion core shared by the `disk-cleaner` binary and its
+//! benchmarks: the parallel walker, the in-memory tree and the analyzer built on
+//! them, plus the platform, permission and protection checks they rely on.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+
+pub mod access;
+pub mod analyzer;
+pub mod cache;
+pub mod error;
+pub mod filter;
+pub mod git;
+pub mod mounts;
+pub mod ncdu;
+pub mod platform;
+pub mod progress;
+pub mod protected;
+pub mod report;
+pub mod serialize;
+pub mod trash;
+pub mod tree;
+pub mod walker;
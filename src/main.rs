@@ -29,18 +29,47 @@
 //!
 //! # Show only directories
 //! disk-cleaner --dirs-only
+//!
+//! # Browse interactively in a full-screen navigator
+//! disk-cleaner ~ --tui
 //! ```
 
-mod analyzer;
+mod export;
 mod file_manager;
-mod platform;
+mod html_report;
+mod journal;
+mod rules;
+mod snapshot;
+mod tui;
+
+use disk_cleaner_rs::{
+    access, analyzer, cache, error, filter, git, mounts, ncdu, platform, progress, protected,
+    report, serialize, trash, tree,
+};
 
-use anyhow::Result;
-use clap::Parser;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::fs::{self, File};
+use std::io::{BufWriter, IsTerminal};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::mpsc;
 
-use analyzer::DiskAnalyzer;
-use file_manager::FileManager;
+use analyzer::{without_nested, DirectoryEntry, DiskAnalyzer, SizeMode};
+use cache::ScanCache;
+use export::{NdjsonRecord, OutputFormat, ScanDocument, ScanSummary};
+use file_manager::{DeleteMode, FileManager};
+use filter::{ExcludedTotals, PathFilter};
+use journal::{Journal, RestoreOutcome};
+use platform::PlatformUtils;
+use progress::{ScanProgress, StatusLine};
+use protected::{ProtectedKind, ProtectedPaths};
+use report::{DeletionReport, EntryKind};
+use rules::RuleSet;
+use snapshot::Snapshot;
 
 /// Command-line interface configuration for the disk cleaner application.
 ///
@@ -73,11 +102,11 @@ struct Cli {
     #[arg(default_value = ".")]
     path: PathBuf,
 
-    /// Maximum directory depth to analyze
+    /// Number of directory levels to display
     ///
-    /// Controls how deep the recursive directory scan goes. Depth 1 means only immediate
-    /// children, depth 2 includes grandchildren, etc. Higher values provide more detail
-    /// but take longer to process.
+    /// Depth 1 shows only immediate children, depth 2 also lists grandchildren under
+    /// their parent, etc. Sizes are always computed over the full subtree regardless
+    /// of this value, so totals are never truncated.
     #[arg(short, long, default_value = "1")]
     depth: usize,
 
@@ -89,6 +118,15 @@ struct Cli {
     #[arg(short, long)]
     min_size: Option<u64>,
 
+    /// Which size drives sorting and --min-size
+    ///
+    /// `apparent` uses file lengths as `ls` reports them. `allocated` uses the blocks
+    /// actually reserved on disk, which is what deleting an entry frees; it differs
+    /// for sparse files, VM images and filesystem-compressed files. Both are shown
+    /// in the summary table.
+    #[arg(long, value_enum, default_value_t = SizeMode::Apparent)]
+    size_mode: SizeMode,
+
     /// Show only directories in results
     ///
     /// Filter results to display directories only, hiding individual files.
@@ -102,6 +140,643 @@ struct Cli {
     /// Cannot be used together with --dirs-only.
     #[arg(long, group = "filter_type")]
     files_only: bool,
+
+    /// Leave out entries matching GLOB, without walking into them (repeatable)
+    ///
+    /// Globs are relative to the scanned directory; one without a `/` matches a name
+    /// at any depth, e.g. `--exclude .git --exclude 'vendor/*'`. Excluded bytes are
+    /// reported apart from the totals.
+    #[arg(long, value_name = "GLOB", conflicts_with = "import")]
+    exclude: Vec<String>,
+
+    /// Count only files matching GLOB, or inside a directory matching it (repeatable)
+    ///
+    /// Uses the same globs as --exclude, which wins where both match.
+    #[arg(long, value_name = "GLOB", conflicts_with = "import")]
+    include: Vec<String>,
+
+    /// Do not walk into other filesystems mounted below the scanned directory
+    ///
+    /// Like `du -x`: directories on another device, such as `/proc`, network shares
+    /// or other disks, are listed with their filesystem type but not scanned.
+    #[arg(short = 'x', long)]
+    one_file_system: bool,
+
+    /// Allow deleting entries that are mount points or contain them
+    ///
+    /// By default such entries are refused, since deleting them would also delete
+    /// what is stored on the mounted filesystem.
+    #[arg(long, conflicts_with = "import")]
+    cross_mounts: bool,
+
+    /// Never delete PATH or anything containing it (repeatable)
+    ///
+    /// Adds to the built-in protection of the filesystem root, home and system
+    /// directories, the current directory and git repository and this program.
+    /// Paths listed in `DISK_CLEANER_PROTECT`, separated like `PATH`, are protected too.
+    #[arg(long, value_name = "PATH")]
+    protect: Vec<PathBuf>,
+
+    /// Do not tag entries as tracked, untracked or ignored by git
+    ///
+    /// Inside a git work tree, entries are tagged using `git ls-files`, which honors
+    /// `.gitignore`, `.git/info/exclude` and the global excludes file, and the summary
+    /// shows how many bytes are ignored and safe to regenerate.
+    #[arg(long)]
+    no_git: bool,
+
+    /// Browse results in a full-screen navigator
+    ///
+    /// Opens an ncdu-style browser where you can drill into directories, go back up,
+    /// and mark entries across different levels. Press `d` to send the marked entries
+    /// to the usual validation and confirmation steps, or `q` to quit.
+    #[arg(short, long)]
+    tui: bool,
+
+    /// How to output the analysis
+    ///
+    /// `table` shows the summary table and the interactive selection. `json` prints
+    /// one JSON document with the scan root, depth, totals, the listed entries and
+    /// any errors hit during the walk, then exits without prompting. `ndjson` prints
+    /// one JSON object per line while the scan is still running, each entry as soon
+    /// as its size is known, followed by a summary line. `csv` and `tsv` print one
+    /// row per listed entry for spreadsheets. `ncdu` prints the whole scan in ncdu's
+    /// export format, to be opened with `ncdu -f` or `--import`.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, conflicts_with_all = ["tui", "rules"])]
+    format: OutputFormat,
+
+    /// Delete permanently instead of moving to the trash
+    ///
+    /// By default deleted entries go to the freedesktop.org trash (the home trash, or
+    /// a `.Trash-$uid` directory at the top of other filesystems), where they can be
+    /// restored from. With this flag they are removed for good.
+    #[arg(long)]
+    permanent: bool,
+
+    /// Show what would be deleted without touching the disk
+    ///
+    /// Runs the usual selection, existence and permission checks, then measures each
+    /// selected entry and prints the report a real run would produce, with the
+    /// space it would free. Nothing is deleted, moved or journaled.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Select entries with the rules in FILE and delete them without prompting
+    ///
+    /// Batch mode for CI and cron jobs. Entries are chosen by the path globs, types,
+    /// sizes and ages declared in the TOML rules file instead of interactively, then
+    /// validated and deleted as usual. Requires --yes unless --dry-run is given.
+    /// Exits with 0 when everything selected was deleted, 3 when nothing matched
+    /// and 4 when some entries could not be deleted.
+    #[arg(long, value_name = "FILE", conflicts_with = "tui")]
+    rules: Option<PathBuf>,
+
+    /// Confirm that batch mode may delete without asking
+    #[arg(long, requires = "rules")]
+    yes: bool,
+
+    /// Browse a scan exported by ncdu instead of scanning (`-` for stdin)
+    ///
+    /// Reads ncdu's JSON export (`ncdu -o FILE`, or `--format ncdu` from this tool)
+    /// taken on any machine. Nothing is deleted from an imported scan; record the
+    /// selection with --save-plan and run it on the machine that holds the files.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["rules", "dry_run", "permanent"])]
+    import: Option<PathBuf>,
+
+    /// Save the selection as a rules file instead of deleting it
+    ///
+    /// The file selects exactly the chosen entries relative to the scanned directory.
+    /// Preview and run it where the files live with
+    /// `disk-cleaner DIR --rules FILE --dry-run` and then `--yes`.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["rules", "dry_run", "permanent"])]
+    save_plan: Option<PathBuf>,
+
+    /// Number of threads reading directories (default: one per CPU)
+    ///
+    /// Directories at every level are shared out between the threads, so a single
+    /// huge subdirectory is scanned as fast as many small ones. Fewer threads put
+    /// less load on slow or network filesystems.
+    #[arg(long, value_name = "N")]
+    threads: Option<NonZeroUsize>,
+
+    /// Read every directory instead of reusing unchanged ones from the last scan
+    ///
+    /// Scans remember each directory's listing under `$XDG_CACHE_HOME/disk-cleaner`
+    /// (`~/.cache/disk-cleaner` by default) and skip directories whose modification
+    /// time has not changed since. A file rewritten in place does not change its
+    /// directory: recently modified files are looked at again, but the new size of
+    /// an older one can be missed until then. Batch mode, snapshots, diffs and
+    /// reports always read everything.
+    #[arg(long)]
+    no_cache: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands that run instead of the interactive analysis.
+#[derive(Subcommand)]
+enum Command {
+    /// List past cleanup sessions, or put a session's trashed entries back
+    ///
+    /// Without arguments, lists every recorded session. Entries are only restored
+    /// when nothing new occupies their original path; conflicts are reported and
+    /// left in the trash.
+    Restore {
+        /// Session to restore, as shown in the list
+        session: Option<String>,
+
+        /// Restore the most recent session
+        #[arg(long, conflicts_with = "session")]
+        last: bool,
+    },
+
+    /// Write a shareable report of a directory's disk usage
+    ///
+    /// The report is a single HTML file with a zoomable treemap, a sortable table of
+    /// the largest entries and a breakdown by file extension. Everything is embedded,
+    /// so it can be mailed around and opened offline.
+    Report {
+        /// Directory to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// File to write the HTML report to
+        #[arg(long, value_name = "FILE")]
+        html: PathBuf,
+
+        /// Which size drives the treemap and the ordering
+        #[arg(long, value_enum, default_value_t = SizeMode::Apparent)]
+        size_mode: SizeMode,
+    },
+
+    /// Save a directory's analysis to a snapshot file for later comparison
+    Snapshot {
+        /// Directory to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// File to write the snapshot to
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        /// Number of directory levels to record
+        #[arg(short, long, default_value = "3")]
+        depth: usize,
+    },
+
+    /// Show what grew, shrank, appeared or disappeared since a snapshot
+    ///
+    /// Compares OLD with NEW, which is either a second snapshot or a directory to
+    /// scan now. Without NEW, the directory OLD was taken of is scanned again.
+    /// Changes are listed largest first.
+    Diff {
+        /// Earlier snapshot
+        old: PathBuf,
+
+        /// Later snapshot, or a directory to scan
+        new: Option<PathBuf>,
+
+        /// Which size is compared
+        #[arg(long, value_enum, default_value_t = SizeMode::Apparent)]
+        size_mode: SizeMode,
+
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Applies the `--min-size`, `--dirs-only` and `--files-only` filters to analyzed entries.
+fn apply_filters(
+    cli: &Cli,
+    analyzer: &DiskAnalyzer,
+    entries: Vec<DirectoryEntry>,
+) -> Vec<DirectoryEntry> {
+    let mut entries = match cli.min_size {
+        Some(min_size) => analyzer.filter_entries(&entries, Some(min_size)),
+        None => entries,
+    };
+
+    if cli.dirs_only {
+        entries.retain(|e| e.is_directory);
+    } else if cli.files_only {
+        entries.retain(|e| !e.is_directory);
+    }
+
+    entries
+}
+
+/// The snapshot file `new`, or a fresh snapshot of the directory `new` (the root of
+/// `old` if not given) taken to the same depth as `old`
+async fn load_or_scan(
+    old: &Snapshot,
+    new: Option<&Path>,
+    size_mode: SizeMode,
+    live_progress: bool,
+) -> Result<Snapshot> {
+    match new {
+        Some(new) if !new.is_dir() => Snapshot::load(new),
+        new => {
+            let path = new.unwrap_or(&old.root);
+            let analyzer = DiskAnalyzer::new(old.depth).with_size_mode(size_mode);
+            let tree =
+                analyze_with_status(&with_live_progress(analyzer, live_progress), path).await?;
+            for error in tree.errors() {
+                eprintln!("Warning: Cannot access {}", error);
+            }
+            Ok(Snapshot::from_tree(&tree, old.depth))
+        }
+    }
+}
+
+/// Compares the snapshot `old` with the snapshot or directory `new` and prints the changes.
+async fn run_diff(old: &Path, new: Option<&Path>, size_mode: SizeMode, json: bool) -> Result<()> {
+    let old = Snapshot::load(old)?;
+    let new = load_or_scan(&old, new, size_mode, !json).await?;
+    let diff = snapshot::diff(&old, &new, size_mode);
+
+    if json {
+        serde_json::to_writer_pretty(std::io::stdout().lock(), &diff)?;
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "📈 {} from {} to {} (depth {})",
+        diff.root.display(),
+        PlatformUtils::local_time(diff.old_taken_at),
+        PlatformUtils::local_time(diff.new_taken_at),
+        diff.depth
+    );
+    if diff.changes.is_empty() {
+        println!("🤷 Nothing changed.");
+        return Ok(());
+    }
+    for change in &diff.changes {
+        let sizes = match (change.old_size, change.new_size) {
+            (Some(old), Some(new)) => format!(
+                " ({} → {})",
+                humansize::format_size(old, humansize::DECIMAL),
+                humansize::format_size(new, humansize::DECIMAL)
+            ),
+            _ => String::new(),
+        };
+        println!(
+            "  {:>10}  {:<7}  {} {}{}",
+            signed_size(change.delta),
+            change.change.label(),
+            if change.kind == EntryKind::Directory {
+                "📁"
+            } else {
+                "📄"
+            },
+            change.path.display(),
+            sizes
+        );
+    }
+    println!(
+        "\n📊 {} changes, total {}",
+        diff.changes.len(),
+        signed_size(diff.total_delta)
+    );
+    Ok(())
+}
+
+/// `bytes` as a human-readable size with an explicit sign
+fn signed_size(bytes: i64) -> String {
+    let sign = if bytes < 0 { "-" } else { "+" };
+    format!(
+        "{}{}",
+        sign,
+        humansize::format_size(bytes.unsigned_abs(), humansize::DECIMAL)
+    )
+}
+
+/// Scans `path` and writes the HTML report to `output`.
+async fn run_report(path: &Path, output: &Path, size_mode: SizeMode) -> Result<()> {
+    println!("🔍 Analyzing: {}", path.display());
+    let analyzer = DiskAnalyzer::new(1).with_size_mode(size_mode);
+    let tree = analyze_with_status(&with_live_progress(analyzer, true), path).await?;
+    for error in tree.errors() {
+        eprintln!("Warning: Cannot access {}", error);
+    }
+
+    let file = File::create(output)
+        .with_context(|| format!("Cannot create report '{}'", output.display()))?;
+    html_report::write_html(BufWriter::new(file), &tree, size_mode)?;
+    println!("📄 Report written to {}", output.display());
+    Ok(())
+}
+
+/// `analyzer`, counting its progress for a status line if `enabled` and stderr is
+/// a terminal
+fn with_live_progress(analyzer: DiskAnalyzer, enabled: bool) -> DiskAnalyzer {
+    if enabled && std::io::stderr().is_terminal() {
+        analyzer.with_progress(Arc::new(ScanProgress::default()))
+    } else {
+        analyzer
+    }
+}
+
+/// Scans `path`, showing a status line on stderr while the scan runs if `analyzer`
+/// counts its progress.
+async fn analyze_with_status(analyzer: &DiskAnalyzer, path: &Path) -> Result<tree::DirTree> {
+    let status = analyzer.progress().cloned().map(StatusLine::start);
+    let tree = analyzer.analyze_tree(path).await;
+    if let Some(status) = status {
+        status.finish().await;
+    }
+    tree
+}
+
+/// Scans while writing NDJSON: every entry that passes the filters as soon as its
+/// size is known, then a summary line.
+async fn run_ndjson(cli: &Cli, analyzer: &DiskAnalyzer) -> Result<ExitCode> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let scan = analyzer.analyze_tree_streaming(&cli.path, sender);
+    let write = async {
+        let mut stdout = std::io::stdout().lock();
+        let mut written = 0;
+        while let Some(entry) = receiver.recv().await {
+            for entry in apply_filters(cli, analyzer, vec![entry]) {
+                export::write_ndjson(&mut stdout, &NdjsonRecord::Entry(&entry))?;
+                written += 1;
+            }
+        }
+        anyhow::Ok(written)
+    };
+    let (tree, written) = tokio::join!(scan, write);
+    let (tree, written) = (tree?, written?);
+
+    for error in tree.errors() {
+        eprintln!("Warning: Cannot access {}", error);
+    }
+    let summary = ScanSummary::new(&tree, written, cli.depth, cli.size_mode);
+    export::write_ndjson(std::io::stdout().lock(), &NdjsonRecord::Summary(summary))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// How a batch (`--rules`) run ended, reported through the process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchStatus {
+    /// Every selected entry was deleted
+    AllDeleted,
+    /// The rules selected nothing that could be deleted
+    NothingToDo,
+    /// At least one selected entry could not be deleted
+    PartialFailure,
+}
+
+impl BatchStatus {
+    /// Status of a run that selected `unwritable` entries it could not attempt
+    /// and produced `report` for the rest
+    fn from_report(unwritable: usize, report: &DeletionReport) -> Self {
+        if unwritable > 0 || report.failed().next().is_some() {
+            BatchStatus::PartialFailure
+        } else if report.entries.is_empty() {
+            BatchStatus::NothingToDo
+        } else {
+            BatchStatus::AllDeleted
+        }
+    }
+
+    fn exit_code(self) -> ExitCode {
+        match self {
+            BatchStatus::AllDeleted => ExitCode::SUCCESS,
+            BatchStatus::NothingToDo => ExitCode::from(3),
+            BatchStatus::PartialFailure => ExitCode::from(4),
+        }
+    }
+}
+
+/// Selects entries with `rules` and deletes them without any prompt.
+fn run_batch(
+    cli: &Cli,
+    analyzer: &DiskAnalyzer,
+    tree: &tree::DirTree,
+    file_manager: &FileManager,
+    rules: &RuleSet,
+) -> Result<ExitCode> {
+    // Filtered inside the selection, so what a rejected directory holds still counts
+    let matches = rules.select(tree, SystemTime::now(), |entry| {
+        !apply_filters(cli, analyzer, vec![entry.clone()]).is_empty()
+    });
+    let candidates: Vec<DirectoryEntry> = matches.iter().map(|m| m.entry.clone()).collect();
+
+    if candidates.is_empty() {
+        println!("🤷 No entries matched the rules.");
+        return Ok(BatchStatus::NothingToDo.exit_code());
+    }
+
+    println!("\n📋 {} entries matched the rules:", candidates.len());
+    for m in &matches {
+        println!(
+            "  {:>8} {} {} ({})",
+            m.entry.size_human,
+            if m.entry.is_directory { "DIR " } else { "FILE" },
+            m.entry.path.display(),
+            m.rule
+        );
+    }
+
+    let (valid, unwritable) =
+        FileManager::split_denied(file_manager.validate_entries(&candidates)?);
+    for (entry, denial) in &unwritable {
+        eprintln!(
+            "Warning: Cannot delete {} ({})",
+            entry.path.display(),
+            denial
+        );
+    }
+
+    if cli.dry_run {
+        println!("\n🔎 Dry run: checking what would be deleted...");
+    } else {
+        println!("\n🗑️  Proceeding with deletion...");
+    }
+    let report = file_manager.delete_entries(&valid)?;
+    print_report(&report);
+    record_session(&report);
+
+    let status = BatchStatus::from_report(unwritable.len(), &report);
+    println!("\n🏁 Batch run finished: {:?}", status);
+    Ok(status.exit_code())
+}
+
+/// Records a deletion run in the journal so it can be undone with `restore`.
+fn record_session(report: &DeletionReport) {
+    let Some(journal) = Journal::for_current_user() else {
+        return;
+    };
+    match journal.record(report) {
+        Ok(Some(session)) => println!(
+            "\n📓 Recorded as session {} (undo with: disk-cleaner restore {})",
+            session.id, session.id
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!("Warning: Could not write the cleanup journal: {:#}", e),
+    }
+}
+
+/// Lists recorded sessions, or restores the one picked by `session` / `last`.
+fn run_restore(session: Option<&str>, last: bool) -> Result<()> {
+    let journal = Journal::for_current_user()
+        .ok_or_else(|| anyhow::anyhow!("Cannot locate the journal: HOME is not set"))?;
+    let sessions = journal.sessions()?;
+
+    let id = match (session, last) {
+        (Some(id), _) => id.to_string(),
+        (None, true) => match sessions.last() {
+            Some(session) => session.id.clone(),
+            None => {
+                println!("📓 No cleanup sessions recorded yet.");
+                return Ok(());
+            }
+        },
+        (None, false) => {
+            if sessions.is_empty() {
+                println!("📓 No cleanup sessions recorded yet.");
+                return Ok(());
+            }
+            println!("📓 Recorded cleanup sessions:");
+            for session in &sessions {
+                println!(
+                    "  {}  {:>3} items  {:>8}  {} restorable",
+                    session.id,
+                    session.entries.len(),
+                    humansize::format_size(session.total_bytes(), humansize::DECIMAL),
+                    session.restorable().count()
+                );
+            }
+            println!("\nRestore one with: disk-cleaner restore <SESSION>");
+            return Ok(());
+        }
+    };
+
+    println!("♻️  Restoring session {}...", id);
+    let results = journal.restore(&id)?;
+    let mut restored = 0;
+    for result in &results {
+        let note = match &result.outcome {
+            RestoreOutcome::Restored => {
+                restored += 1;
+                "✅ restored".to_string()
+            }
+            RestoreOutcome::Conflict => "⚠️  skipped: something else now exists there".to_string(),
+            RestoreOutcome::Missing => "❌ no longer in the trash".to_string(),
+            RestoreOutcome::Permanent => "❌ was deleted permanently".to_string(),
+            RestoreOutcome::AlreadyRestored => "already restored".to_string(),
+            RestoreOutcome::Failed(error) => format!("❌ failed: {}", error),
+        };
+        println!("  {} ({})", result.original.display(), note);
+    }
+    println!("\n🏁 Restored {} of {} items.", restored, results.len());
+    Ok(())
+}
+
+/// Prints what `--exclude` and `--include` left out of the sizes shown, if anything.
+fn print_excluded(excluded: &ExcludedTotals) {
+    if excluded.is_empty() {
+        return;
+    }
+    println!(
+        "\n🚫 Excluded and not counted above: {} files ({}), {} directories not scanned",
+        excluded.files,
+        humansize::format_size(excluded.apparent_bytes, humansize::DECIMAL),
+        excluded.directories
+    );
+}
+
+/// Warns that file sizes may be stale if directories were reused from the scan cache.
+fn print_cache_note(tree: &tree::DirTree) {
+    let reused = tree.cached_directories();
+    if reused == 0 {
+        return;
+    }
+    println!(
+        "\n♻️  Sizes may be stale: {} directories were reused from the last scan \
+         (--no-cache reads everything)",
+        reused
+    );
+}
+
+/// Prints the per-entry results and space totals of a deletion run (or of a dry run,
+/// where every result is a prediction).
+fn print_report(report: &DeletionReport) {
+    let freed = if report.dry_run {
+        "would be freed"
+    } else {
+        "freed"
+    };
+
+    let deleted: Vec<_> = report.deleted().collect();
+    if !deleted.is_empty() {
+        if report.dry_run {
+            println!("\n✅ Would delete {} items:", deleted.len());
+        } else {
+            println!("\n✅ Successfully deleted {} items:", deleted.len());
+        }
+        for result in &deleted {
+            let size = humansize::format_size(result.bytes, humansize::DECIMAL);
+            match &result.trashed_to {
+                Some(destination) => println!(
+                    "  🗑️  {} ({} moved to {})",
+                    result.path.display(),
+                    size,
+                    destination.display()
+                ),
+                None => println!("  🗑️  {} ({} {})", result.path.display(), size, freed),
+            }
+        }
+    }
+
+    let failed: Vec<_> = report.failed().collect();
+    if !failed.is_empty() {
+        if report.dry_run {
+            println!("\n❌ Would fail to delete {} items:", failed.len());
+        } else {
+            println!("\n❌ Failed to delete {} items:", failed.len());
+        }
+        for result in &failed {
+            println!(
+                "  ⚠️  {} ({})",
+                result.path.display(),
+                result.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+    }
+
+    // Freed space as measured per entry right before it was removed
+    let freed_bytes = report.bytes_freed();
+    if freed_bytes > 0 {
+        let freed_human = humansize::format_size(freed_bytes, humansize::DECIMAL);
+        println!("\n💾 Total space {}: {}", freed, freed_human);
+    }
+
+    let trashed_bytes = report.bytes_trashed();
+    if trashed_bytes > 0 {
+        println!(
+            "\n♻️  {} {} to the trash; it is freed once the trash is emptied.",
+            humansize::format_size(trashed_bytes, humansize::DECIMAL),
+            if report.dry_run {
+                "would move"
+            } else {
+                "moved"
+            }
+        );
+    }
+
+    // Cross-check against what each filesystem itself reports
+    for delta in &report.filesystems {
+        println!(
+            "📉 Filesystem holding {}: {} free before, {} after ({} gained)",
+            delta.probe.display(),
+            humansize::format_size(delta.free_before, humansize::DECIMAL),
+            humansize::format_size(delta.free_after, humansize::DECIMAL),
+            humansize::format_size(delta.freed_bytes(), humansize::DECIMAL)
+        );
+    }
 }
 
 /// Application entry point.
@@ -116,7 +791,8 @@ struct Cli {
 ///
 /// # Returns
 ///
-/// `Ok(())` on successful completion, or an error if any step fails.
+/// The exit code on completion (always success outside batch mode; see
+/// [`BatchStatus`]), or an error if any step fails.
 ///
 /// # Errors
 ///
@@ -125,63 +801,243 @@ struct Cli {
 /// - File system operations fail due to permissions
 /// - User interface interactions fail
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
 
+    match &cli.command {
+        Some(Command::Restore { session, last }) => {
+            run_restore(session.as_deref(), *last)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Report {
+            path,
+            html,
+            size_mode,
+        }) => {
+            run_report(path, html, *size_mode).await?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Snapshot {
+            path,
+            output,
+            depth,
+        }) => {
+            let analyzer = with_live_progress(DiskAnalyzer::new(*depth), true);
+            let tree = analyze_with_status(&analyzer, path).await?;
+            for error in tree.errors() {
+                eprintln!("Warning: Cannot access {}", error);
+            }
+            let snapshot = Snapshot::from_tree(&tree, *depth);
+            snapshot.save(output)?;
+            println!(
+                "📸 Saved {} entries of {} to {}",
+                snapshot.entries.len(),
+                path.display(),
+                output.display()
+            );
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Diff {
+            old,
+            new,
+            size_mode,
+            json,
+        }) => {
+            run_diff(old, new.as_deref(), *size_mode, *json).await?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {}
+    }
+
+    // Batch mode never prompts, so it must be confirmed up front
+    let rules = match &cli.rules {
+        Some(path) => {
+            if !cli.yes && !cli.dry_run {
+                anyhow::bail!(
+                    "--rules deletes without asking; pass --yes to confirm or --dry-run to preview"
+                );
+            }
+            Some(RuleSet::from_file(path)?)
+        }
+        None => None,
+    };
+
     // Initialize components
-    let analyzer = DiskAnalyzer::new(cli.depth);
-    let file_manager = FileManager::new();
+    let mut analyzer = DiskAnalyzer::new(cli.depth)
+        .with_size_mode(cli.size_mode)
+        .with_git_status(!cli.no_git)
+        .with_one_file_system(cli.one_file_system);
+    if let Some(threads) = cli.threads {
+        analyzer = analyzer.with_threads(threads);
+    }
+    if let Some(filter) = PathFilter::new(&cli.path, &cli.exclude, &cli.include)? {
+        analyzer = analyzer.with_filter(filter);
+    }
+    // Machine-readable formats keep the terminal free of anything but warnings
+    analyzer = with_live_progress(analyzer, cli.format == OutputFormat::Table);
+    // Rules may select by age, which a stale cached file would get wrong
+    if !cli.no_cache && rules.is_none() {
+        if let Some(cache) = ScanCache::for_current_user() {
+            analyzer = analyzer.with_cache(cache);
+        }
+    }
+    let delete_mode = if cli.permanent {
+        DeleteMode::Permanent
+    } else {
+        DeleteMode::Trash
+    };
+    let mut protected = ProtectedPaths::new();
+    let configured = std::env::var_os("DISK_CLEANER_PROTECT");
+    let configured = configured.iter().flat_map(std::env::split_paths);
+    for path in cli.protect.iter().cloned().chain(configured) {
+        protected.protect(&path, ProtectedKind::Configured);
+    }
 
-    // Display header
-    println!("🔍 Disk Cleaner - Interactive Directory Analysis");
-    println!("📁 Analyzing: {}", cli.path.display());
+    // Display header; machine-readable formats keep stdout for the document
+    if cli.format == OutputFormat::Table {
+        println!("🔍 Disk Cleaner - Interactive Directory Analysis");
+        match &cli.import {
+            Some(export) => println!("📥 Importing: {}", export.display()),
+            None => println!("📁 Analyzing: {}", cli.path.display()),
+        }
 
-    if cli.depth > 1 {
-        println!("📊 Max depth: {}", cli.depth);
+        if cli.depth > 1 {
+            println!("📊 Display depth: {}", cli.depth);
+        }
     }
 
-    // Analyze directory
-    let mut entries = analyzer.analyze_directory(&cli.path).await?;
+    if cli.format == OutputFormat::Ndjson && cli.import.is_none() {
+        return run_ndjson(&cli, &analyzer).await;
+    }
 
-    // Apply filters
-    if let Some(min_size) = cli.min_size {
-        entries = analyzer.filter_entries(&entries, Some(min_size));
+    // Scan once into an in-memory tree; every view below is built from it
+    let tree = match &cli.import {
+        Some(export) => {
+            let tree = analyzer.import_ncdu(export)?;
+            if cli.format == OutputFormat::Table {
+                println!("📁 Scan of: {}", tree.path(tree.root()).display());
+            }
+            tree
+        }
+        None => analyze_with_status(&analyzer, &cli.path).await?,
+    };
+    for error in tree.errors() {
+        eprintln!("Warning: Cannot access {}", error);
     }
+    protected.protect(&tree.path(tree.root()), ProtectedKind::ScanRoot);
+    let file_manager = FileManager::new()
+        .with_delete_mode(delete_mode)
+        .with_dry_run(cli.dry_run)
+        .with_cross_mounts(cli.cross_mounts)
+        .with_protected(protected);
 
-    if cli.dirs_only {
-        entries.retain(|e| e.is_directory);
-    } else if cli.files_only {
-        entries.retain(|e| !e.is_directory);
+    if let Some(rules) = &rules {
+        return run_batch(&cli, &analyzer, &tree, &file_manager, rules);
     }
 
-    if entries.is_empty() {
-        println!("🤷 No entries found matching the criteria.");
-        return Ok(());
+    let entries = apply_filters(&cli, &analyzer, analyzer.visible_entries(&tree));
+
+    match cli.format {
+        OutputFormat::Table => {}
+        OutputFormat::Ndjson => {
+            // Imported scans are complete already, so there is nothing to stream
+            let mut stdout = std::io::stdout().lock();
+            for entry in &entries {
+                export::write_ndjson(&mut stdout, &NdjsonRecord::Entry(entry))?;
+            }
+            let summary = ScanSummary::new(&tree, entries.len(), cli.depth, cli.size_mode);
+            export::write_ndjson(&mut stdout, &NdjsonRecord::Summary(summary))?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        OutputFormat::Ncdu => {
+            ncdu::write_ncdu(std::io::stdout().lock(), &tree)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        OutputFormat::Json => {
+            let document = ScanDocument::new(&tree, &entries, cli.depth, cli.size_mode);
+            export::write_json(std::io::stdout().lock(), &document)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        OutputFormat::Csv => {
+            export::write_csv(std::io::stdout().lock(), &entries)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        OutputFormat::Tsv => {
+            export::write_tsv(std::io::stdout().lock(), &entries)?;
+            return Ok(ExitCode::SUCCESS);
+        }
     }
 
-    // Display summary
-    file_manager.display_summary(&entries);
+    if entries.is_empty() && !cli.tui {
+        println!("🤷 No entries found matching the criteria.");
+        return Ok(ExitCode::SUCCESS);
+    }
 
     // Interactive selection
-    println!("\n🎯 Select items for deletion:");
-    let selected = file_manager.select_entries(&entries)?;
+    let selected = if cli.tui {
+        tui::browse(&tree, cli.size_mode, |children| {
+            apply_filters(&cli, &analyzer, children)
+        })?
+    } else {
+        // Display summary
+        file_manager.display_summary(&entries);
+        print_excluded(tree.excluded());
+        print_cache_note(&tree);
+
+        if cli.save_plan.is_some() || cli.import.is_some() {
+            println!("\n🎯 Select items for the cleanup plan:");
+        } else {
+            println!("\n🎯 Select items for deletion:");
+        }
+        file_manager.select_entries(&entries)?
+    };
 
     if selected.is_empty() {
         println!("👋 No items selected. Exiting.");
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Selecting a directory already covers everything listed beneath it
+    let selected = without_nested(&selected);
+
+    if let Some(plan) = &cli.save_plan {
+        let root = tree.path(tree.root());
+        fs::write(plan, rules::plan_toml(&root, &selected)?)
+            .with_context(|| format!("Cannot write plan '{}'", plan.display()))?;
+        println!(
+            "\n📝 Saved {} items to {}. Preview and run it where the files live:",
+            selected.len(),
+            plan.display()
+        );
+        println!(
+            "  disk-cleaner {} --rules {} --dry-run",
+            root.display(),
+            plan.display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if cli.import.is_some() {
+        println!("\nℹ️  Nothing is deleted from an imported scan. Selected:");
+        for entry in &selected {
+            println!("  {} {}", entry.size_human, entry.path.display());
+        }
+        println!("  Pass --save-plan FILE to run this selection where the files live.");
+        return Ok(ExitCode::SUCCESS);
     }
 
     // Validate entries still exist and check permissions
-    let valid_selected = file_manager.validate_entries(&selected);
-    let unwritable = file_manager.get_unwritable_entries(&selected);
+    let (valid_selected, unwritable) =
+        FileManager::split_denied(file_manager.validate_entries(&selected)?);
 
     if !unwritable.is_empty() {
         println!("\n⚠️  Warning: The following items cannot be deleted (permission denied):");
-        for entry in &unwritable {
+        for (entry, denial) in &unwritable {
             println!(
-                "  {} {}",
+                "  {} {}: {}",
                 if entry.is_directory { "📁" } else { "📄" },
-                entry.path.display()
+                entry.path.display(),
+                denial
             );
         }
         println!("  You may need administrator/root privileges to delete these items.\n");
@@ -203,52 +1059,33 @@ async fn main() -> Result<()> {
 
     if valid_selected.is_empty() {
         println!("❌ No valid items to delete.");
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // A dry run needs no confirmation: it reports what would happen and stops
+    if cli.dry_run {
+        println!("\n🔎 Dry run: checking what would be deleted...");
+        let report = file_manager.delete_entries(&valid_selected)?;
+        print_report(&report);
+        println!("\n🏁 Dry run completed; nothing was changed.");
+        return Ok(ExitCode::SUCCESS);
     }
 
     // Confirm deletion
     if file_manager.confirm_deletion(&valid_selected)? {
         println!("\n🗑️  Proceeding with deletion...");
 
-        let (deleted, failed) = file_manager.delete_entries(&valid_selected)?;
-
-        // Display results
-        if !deleted.is_empty() {
-            println!("\n✅ Successfully deleted {} items:", deleted.len());
-            for item in &deleted {
-                println!("  🗑️  {}", item);
-            }
-        }
-
-        if !failed.is_empty() {
-            println!("\n❌ Failed to delete {} items:", failed.len());
-            for item in &failed {
-                println!("  ⚠️  {}", item);
-            }
-        }
+        let report = file_manager.delete_entries(&valid_selected)?;
+        print_report(&report);
 
-        // Calculate freed space
-        let freed_bytes: u64 = valid_selected
-            .iter()
-            .filter(|entry| {
-                deleted
-                    .iter()
-                    .any(|d| d.contains(&entry.path.to_string_lossy().to_string()))
-            })
-            .map(|entry| entry.size_bytes)
-            .sum();
-
-        if freed_bytes > 0 {
-            let freed_human = humansize::format_size(freed_bytes, humansize::DECIMAL);
-            println!("\n💾 Total space freed: {}", freed_human);
-        }
+        record_session(&report);
 
         println!("\n🏁 Operation completed!");
     } else {
         println!("❌ Deletion cancelled by user.");
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 #[cfg(test)]
@@ -264,6 +1101,228 @@ mod tests {
         assert_eq!(cli.min_size, None);
         assert!(!cli.dirs_only);
         assert!(!cli.files_only);
+        assert!(!cli.tui);
+        assert_eq!(cli.size_mode, SizeMode::Apparent);
+        assert!(!cli.permanent);
+        assert!(!cli.dry_run);
+        assert_eq!(cli.format, OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_cli_permanent_flag() {
+        let cli = Cli::try_parse_from(["disk-cleaner", "--permanent"]).unwrap();
+        assert!(cli.permanent);
+    }
+
+    #[test]
+    fn test_cli_restore_subcommand() {
+        let cli = Cli::try_parse_from(["disk-cleaner", "restore"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Restore {
+                session: None,
+                last: false
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["disk-cleaner", "restore", "2025-01-02-03-04-05"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Restore { session: Some(ref id), .. }) if id == "2025-01-02-03-04-05"
+        ));
+
+        assert!(Cli::try_parse_from(["disk-cleaner", "restore", "x", "--last"]).is_err());
+
+        // Plain paths are still analyzed
+        let cli = Cli::try_parse_from(["disk-cleaner", "/tmp"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.path, PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_cli_snapshot_and_diff_subcommands() {
+        let cli =
+            Cli::try_parse_from(["disk-cleaner", "snapshot", "/data", "-o", "week1.json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Snapshot { ref path, ref output, depth: 3 })
+                if path == Path::new("/data") && output == Path::new("week1.json")
+        ));
+        assert!(Cli::try_parse_from(["disk-cleaner", "snapshot", "/data"]).is_err());
+
+        let cli = Cli::try_parse_from(["disk-cleaner", "diff", "week1.json", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Diff {
+                new: None,
+                json: true,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_diff_against_live_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path().join("data");
+        fs::create_dir_all(dir.join("logs")).unwrap();
+        fs::write(dir.join("logs/app.log"), vec![b'x'; 1000]).unwrap();
+        let analyzer = DiskAnalyzer::new(2);
+        let old = Snapshot::from_tree(&analyzer.analyze_tree(&dir).await.unwrap(), 2);
+
+        fs::write(dir.join("logs/app.log"), vec![b'x'; 3000]).unwrap();
+        fs::write(dir.join("new.bin"), vec![b'x'; 500]).unwrap();
+
+        // A directory is scanned rather than read as a snapshot, and so is the
+        // snapshot's own root when none is given
+        for new in [Some(dir.as_path()), None] {
+            let new = load_or_scan(&old, new, SizeMode::Apparent, false)
+                .await
+                .unwrap();
+            let diff = snapshot::diff(&old, &new, SizeMode::Apparent);
+            assert_eq!(diff.total_delta, 2500);
+            let changes: Vec<_> = diff
+                .changes
+                .iter()
+                .map(|c| (c.path.clone(), c.change.label(), c.delta))
+                .collect();
+            assert_eq!(
+                changes,
+                vec![
+                    (PathBuf::from("logs"), "grew", 2000),
+                    (PathBuf::from("logs/app.log"), "grew", 2000),
+                    (PathBuf::from("new.bin"), "added", 500),
+                ]
+            );
+        }
+
+        // Anything else must be a snapshot
+        let error = load_or_scan(&old, Some(&dir.join("new.bin")), SizeMode::Apparent, false)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("is not a snapshot"));
+    }
+
+    #[test]
+    fn test_cli_report_subcommand() {
+        let cli = Cli::try_parse_from(["disk-cleaner", "report", "--html", "out.html"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Report { ref path, ref html, .. })
+                if path == Path::new(".") && html == Path::new("out.html")
+        ));
+        // `--html` is required
+        assert!(Cli::try_parse_from(["disk-cleaner", "report", "/tmp"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_batch_flags() {
+        let cli =
+            Cli::try_parse_from(["disk-cleaner", "/builds", "--rules", "clean.toml", "--yes"])
+                .unwrap();
+        assert_eq!(cli.rules, Some(PathBuf::from("clean.toml")));
+        assert!(cli.yes);
+
+        // --yes only makes sense together with rules, and rules never open the TUI
+        assert!(Cli::try_parse_from(["disk-cleaner", "--yes"]).is_err());
+        assert!(Cli::try_parse_from(["disk-cleaner", "--rules", "r.toml", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn test_batch_status() {
+        use report::{DeletionRecord, EntryKind};
+        use std::time::Duration;
+
+        let deleted = DeletionRecord::deleted(
+            std::path::Path::new("/builds/target"),
+            EntryKind::Directory,
+            100,
+            Duration::ZERO,
+        );
+        let failed = DeletionRecord::failed(
+            std::path::Path::new("/builds/gone"),
+            EntryKind::File,
+            &error::DeleteError::NotFound {
+                path: PathBuf::from("/builds/gone"),
+            },
+            Duration::ZERO,
+        );
+        let report = |entries: Vec<DeletionRecord>| DeletionReport {
+            entries,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            BatchStatus::from_report(0, &report(vec![deleted.clone()])),
+            BatchStatus::AllDeleted
+        );
+        assert_eq!(
+            BatchStatus::from_report(0, &report(vec![])),
+            BatchStatus::NothingToDo
+        );
+        assert_eq!(
+            BatchStatus::from_report(0, &report(vec![deleted.clone(), failed])),
+            BatchStatus::PartialFailure
+        );
+        assert_eq!(
+            BatchStatus::from_report(1, &report(vec![deleted])),
+            BatchStatus::PartialFailure
+        );
+    }
+
+    #[test]
+    fn test_cli_format() {
+        let cli = Cli::try_parse_from(["disk-cleaner", "--format", "json"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Json);
+        let cli = Cli::try_parse_from(["disk-cleaner", "--format", "ndjson"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Ndjson);
+        let cli = Cli::try_parse_from(["disk-cleaner", "--format", "tsv"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Tsv);
+
+        assert!(Cli::try_parse_from(["disk-cleaner", "--format", "json", "--tui"]).is_err());
+        assert!(Cli::try_parse_from(["disk-cleaner", "--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_import_and_plan() {
+        let cli = Cli::try_parse_from([
+            "disk-cleaner",
+            "--import",
+            "scan.json",
+            "--save-plan",
+            "plan.toml",
+        ])
+        .unwrap();
+        assert_eq!(cli.import, Some(PathBuf::from("scan.json")));
+        assert_eq!(cli.save_plan, Some(PathBuf::from("plan.toml")));
+
+        // Imported scans and plans never delete on this machine
+        for flag in ["--dry-run", "--permanent"] {
+            assert!(Cli::try_parse_from(["disk-cleaner", "--import", "-", flag]).is_err());
+            assert!(Cli::try_parse_from(["disk-cleaner", "--save-plan", "p", flag]).is_err());
+        }
+        assert!(
+            Cli::try_parse_from(["disk-cleaner", "--import", "-", "--rules", "r", "--yes"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_size_mode() {
+        let cli = Cli::parse_from(["disk-cleaner", "--size-mode", "allocated"]);
+        assert_eq!(cli.size_mode, SizeMode::Allocated);
+
+        let result = Cli::try_parse_from(["disk-cleaner", "--size-mode", "bogus"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_threads() {
+        let cli = Cli::parse_from(["disk-cleaner", "--threads", "4"]);
+        assert_eq!(cli.threads, NonZeroUsize::new(4));
+        assert_eq!(Cli::parse_from(["disk-cleaner"]).threads, None);
+
+        assert!(Cli::try_parse_from(["disk-cleaner", "--threads", "0"]).is_err());
     }
 
     #[test]
@@ -285,6 +1344,31 @@ mod tests {
         assert!(!cli.files_only);
     }
 
+    #[test]
+    fn test_cli_tui_flag() {
+        let cli = Cli::parse_from(["disk-cleaner", "/tmp", "--tui"]);
+        assert!(cli.tui);
+
+        let cli = Cli::parse_from(["disk-cleaner", "-t"]);
+        assert!(cli.tui);
+    }
+
+    #[test]
+    fn test_apply_filters() {
+        let analyzer = DiskAnalyzer::new(1);
+        let entries = vec![
+            DirectoryEntry::new(PathBuf::from("big_dir"), 5000, true),
+            DirectoryEntry::new(PathBuf::from("big_file"), 4000, false),
+            DirectoryEntry::new(PathBuf::from("small_dir"), 10, true),
+        ];
+
+        let cli = Cli::parse_from(["disk-cleaner", "--min-size", "1000", "--dirs-only"]);
+        let filtered = apply_filters(&cli, &analyzer, entries);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].path, PathBuf::from("big_dir"));
+    }
+
     #[test]
     fn test_cli_conflicting_flags_prevented() {
         use clap::error::ErrorKind;
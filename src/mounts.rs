@@ -0,0 +1,198 @@
+//! # Mount Points
+//!
+//! Finds where other filesystems are mounted inside a scanned tree, so bind
+//! mounts, network shares and pseudo filesystems such as `/proc` can be shown for
+//! what they are and are never deleted by accident along with their parent.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Sources
+//!
+//! On Linux the mount table is read from `/proc/self/mountinfo`, which also lists
+//! bind mounts of a directory on the same device. Elsewhere the table is empty and
+//! a mount point is only recognized by its device differing from its parent's.
+
+use crate::platform::PlatformUtils;
+use crate::tree::{DirTree, NodeId};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One mounted filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    /// Where the filesystem is mounted
+    pub path: PathBuf,
+    /// Filesystem type, such as `ext4`, `nfs4` or `proc`
+    pub fs_type: String,
+}
+
+/// The filesystems mounted on this system.
+#[derive(Debug, Clone, Default)]
+pub struct MountTable {
+    mounts: Vec<Mount>,
+}
+
+impl MountTable {
+    /// The current mount table; empty where it cannot be read
+    pub fn load() -> Self {
+        fs::read_to_string("/proc/self/mountinfo")
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    /// Table from the contents of a `mountinfo` file, skipping malformed lines.
+    ///
+    /// Each line reads `ID PARENT MAJOR:MINOR ROOT MOUNT_POINT OPTIONS [TAGS...] -
+    /// TYPE SOURCE SUPER_OPTIONS`; only the mount point and type are kept.
+    fn parse(text: &str) -> Self {
+        let mounts = text
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(' ').collect();
+                let separator = fields.iter().position(|&field| field == "-")?;
+                Some(Mount {
+                    path: PlatformUtils::path_from_bytes(unescape(fields.get(4)?)),
+                    fs_type: fields.get(separator + 1)?.to_string(),
+                })
+            })
+            .collect();
+        Self { mounts }
+    }
+
+    /// Mounts at `path` or below it, in mount order. `path` must be canonical.
+    pub fn mounts_within<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Mount> {
+        self.mounts
+            .iter()
+            .filter(move |mount| mount.path.starts_with(path))
+    }
+}
+
+/// Marks every node of `tree` that is a mount point in `table` with its filesystem type.
+pub fn tag_tree(tree: &mut DirTree, table: &MountTable) {
+    tag_subtree(tree, tree.root(), table);
+}
+
+/// Marks the mount points in `table` at or below the node `id` of `tree`.
+pub fn tag_subtree(tree: &mut DirTree, id: NodeId, table: &MountTable) {
+    let (Ok(root), Ok(subtree)) = (
+        fs::canonicalize(tree.path(tree.root())),
+        fs::canonicalize(tree.path(id)),
+    ) else {
+        return;
+    };
+    for mount in table.mounts_within(&subtree) {
+        let Ok(relative) = mount.path.strip_prefix(&root) else {
+            continue;
+        };
+        // A later mount on the same path hides the earlier one
+        if let Some(id) = tree.find(relative) {
+            tree.node_mut(id).mount = Some(mount.fs_type.clone());
+        }
+    }
+}
+
+/// `field` with the octal escapes of `mountinfo` (`\040` for a space) decoded
+fn unescape(field: &str) -> Vec<u8> {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|_| bytes[i] == b'\\');
+        match escape
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok())
+        {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 4;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// A mount point at or below `path` that deleting `path` would reach into, if any.
+///
+/// A symbolic link is removed without following it, so it never reaches one.
+pub fn mount_within(path: &Path, table: &MountTable) -> Option<PathBuf> {
+    let metadata = path.symlink_metadata().ok()?;
+    if metadata.file_type().is_symlink() {
+        return None;
+    }
+
+    let parent = match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => return Some(path.to_path_buf()),
+    };
+    let device = |metadata: &fs::Metadata| PlatformUtils::file_id(metadata).map(|id| id.device);
+    let parent_device = parent.metadata().ok().and_then(|meta| device(&meta));
+    if device(&metadata).is_some_and(|own| Some(own) != parent_device) {
+        return Some(path.to_path_buf());
+    }
+
+    // The table holds canonical paths
+    let canonical = PlatformUtils::canonical_entry(path)?;
+    table
+        .mounts
+        .iter()
+        .find(|mount| mount.path.starts_with(&canonical))
+        .map(|mount| mount.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+41 22 0:38 / /srv/nfs\\040share rw,relatime shared:20 - nfs4 server:/export rw,vers=4.2
+42 22 259:2 /data /home/user/data rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+garbage line
+";
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let table = MountTable::parse(MOUNTINFO);
+        assert_eq!(table.mounts.len(), 4);
+        assert_eq!(
+            table.mounts[2],
+            Mount {
+                path: PathBuf::from("/srv/nfs share"),
+                fs_type: "nfs4".to_string(),
+            }
+        );
+
+        let home: Vec<_> = table.mounts_within(Path::new("/home")).collect();
+        assert_eq!(home.len(), 1);
+        assert_eq!(home[0].path, Path::new("/home/user/data"));
+        assert_eq!(home[0].fs_type, "ext4");
+        assert_eq!(table.mounts_within(Path::new("/srv/nfs")).count(), 0);
+    }
+
+    #[test]
+    fn test_mount_within() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = fs::canonicalize(temp_dir.path()).unwrap();
+        fs::create_dir_all(base.join("project/mnt")).unwrap();
+        fs::create_dir_all(base.join("other")).unwrap();
+
+        let table = MountTable {
+            mounts: vec![Mount {
+                path: base.join("project/mnt"),
+                fs_type: "tmpfs".to_string(),
+            }],
+        };
+        assert_eq!(
+            mount_within(&base.join("project"), &table),
+            Some(base.join("project/mnt"))
+        );
+        assert_eq!(mount_within(&base.join("other"), &table), None);
+        assert_eq!(mount_within(&base.join("missing"), &table), None);
+    }
+}
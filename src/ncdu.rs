@@ -0,0 +1,379 @@
+//! # ncdu Export Format
+//!
+//! Reads and writes scans in the JSON export format of ncdu (`ncdu -o` and
+//! `ncdu -f`), so a scan taken on a server with either tool can be browsed and
+//! planned on another machine.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Format
+//!
+//! ```json
+//! [1, 2, {"progname": "ncdu", "progver": "1.19", "timestamp": 1700000000},
+//!   [{"name": "/srv", "dsize": 4096},
+//!     {"name": "notes.txt", "asize": 120, "dsize": 4096, "mtime": 1700000000},
+//!     [{"name": "logs", "dsize": 4096, "read_error": true},
+//!       {"name": "app.log", "asize": 5000, "dsize": 8192, "ino": 12, "dev": 2049,
+//!        "hlnkc": true, "nlink": 2}]]]
+//! ```
+//!
+//! A directory is an array holding its own info object followed by its children;
+//! a file is just its info object. Sizes are per item, not cumulative; totals are
+//! rebuilt on import.
+//!
+//! ## Limitations
+//!
+//! - Names are written as UTF-8; bytes that are not valid UTF-8 are replaced, both
+//!   when writing and when reading exports made by ncdu.
+//! - Directories contribute no apparent size of their own, as in a live scan, so
+//!   the `asize` of imported directories is ignored.
+//! - Excluded entries (other filesystems, `--exclude` patterns) are left out.
+
+use crate::platform::FileId;
+use crate::tree::{DirTree, NodeId};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::ops::Not;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Major version of the export format; readers reject other majors
+const MAJOR_VERSION: u64 = 1;
+/// Minor version of the export format written by [`write_ncdu`]
+const MINOR_VERSION: u64 = 2;
+
+/// Information about one item, shared by files and directories.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ItemInfo {
+    name: String,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    asize: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    dsize: u64,
+    /// Device number, inherited from the closest ancestor when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dev: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ino: Option<u64>,
+    /// True for files with more than one hard link
+    #[serde(default, skip_serializing_if = "Not::not")]
+    hlnkc: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nlink: Option<u64>,
+    #[serde(default, skip_serializing_if = "Not::not")]
+    read_error: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtime: Option<u64>,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Writes `tree` as an ncdu export.
+pub fn write_ncdu<W: Write>(mut writer: W, tree: &DirTree) -> Result<()> {
+    let links: HashMap<NodeId, (FileId, u64)> = tree
+        .hard_links()
+        .map(|(node, file_id, link_count)| (node, (file_id, link_count)))
+        .collect();
+    let duplicates = cross_child_duplicates(tree, &links);
+    let header = serde_json::json!({
+        "progname": env!("CARGO_PKG_NAME"),
+        "progver": env!("CARGO_PKG_VERSION"),
+        "timestamp": unix_secs(Some(SystemTime::now())),
+    });
+    write!(writer, "[{},{},", MAJOR_VERSION, MINOR_VERSION)?;
+    serde_json::to_writer(&mut writer, &header)?;
+
+    // Directories are arrays that stay open while their children are written
+    enum Step {
+        Item(NodeId),
+        Close,
+    }
+    let mut steps = vec![Step::Item(tree.root())];
+    while let Some(step) = steps.pop() {
+        let id = match step {
+            Step::Close => {
+                writer.write_all(b"]")?;
+                continue;
+            }
+            Step::Item(id) => id,
+        };
+        let is_directory = tree.node(id).is_directory;
+        writer.write_all(if is_directory { b",[" } else { b"," })?;
+        serde_json::to_writer(&mut writer, &item_info(tree, id, &links, &duplicates))?;
+        if is_directory {
+            steps.push(Step::Close);
+            steps.extend(
+                tree.children(id)
+                    .iter()
+                    .rev()
+                    .map(|&child| Step::Item(child)),
+            );
+        }
+    }
+
+    writer.write_all(b"]\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Allocated bytes the hard-link roll-up removed from each directory because links
+/// to one inode sit in several of its children
+fn cross_child_duplicates(
+    tree: &DirTree,
+    links: &HashMap<NodeId, (FileId, u64)>,
+) -> HashMap<NodeId, u64> {
+    let mut groups: HashMap<FileId, Vec<NodeId>> = HashMap::new();
+    for (&node, &(file_id, _)) in links {
+        groups.entry(file_id).or_default().push(node);
+    }
+
+    let mut duplicates = HashMap::new();
+    for nodes in groups.values() {
+        let allocated = tree.node(nodes[0]).allocated_bytes;
+        // Which children of each directory the links are reached through
+        let mut reached_through: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+        for &node in nodes {
+            let mut child = node;
+            while let Some(parent) = tree.node(child).parent {
+                reached_through.entry(parent).or_default().insert(child);
+                child = parent;
+            }
+        }
+        for (directory, children) in reached_through {
+            *duplicates.entry(directory).or_default() += allocated * (children.len() as u64 - 1);
+        }
+    }
+    duplicates
+}
+
+/// The ncdu info object for node `id`
+fn item_info(
+    tree: &DirTree,
+    id: NodeId,
+    links: &HashMap<NodeId, (FileId, u64)>,
+    duplicates: &HashMap<NodeId, u64>,
+) -> ItemInfo {
+    let node = tree.node(id);
+    let name = match node.parent {
+        Some(_) => node.name.to_string_lossy().into_owned(),
+        None => tree.path(id).to_string_lossy().into_owned(),
+    };
+    let mut info = ItemInfo {
+        name,
+        read_error: !node.errors.is_empty(),
+        // Directory times are the newest inside them, which is what a re-import
+        // rolls up to anyway
+        mtime: unix_secs(node.modified),
+        ..ItemInfo::default()
+    };
+
+    if node.is_directory {
+        // Only the directory's own blocks: its total without its children's
+        let children: u64 = tree
+            .children(id)
+            .iter()
+            .map(|&child| tree.node(child).allocated_bytes)
+            .sum();
+        let total = node.allocated_bytes + duplicates.get(&id).copied().unwrap_or(0);
+        info.dsize = total.saturating_sub(children);
+    } else {
+        info.asize = node.size_bytes;
+        info.dsize = node.allocated_bytes;
+        if let Some(&(file_id, link_count)) = links.get(&id) {
+            info.dev = Some(file_id.device);
+            info.ino = Some(file_id.inode);
+            info.hlnkc = true;
+            info.nlink = Some(link_count);
+        }
+    }
+    info
+}
+
+/// Reads an ncdu export into a finalized tree with hard links resolved.
+pub fn read_ncdu<R: Read>(mut reader: R) -> Result<DirTree> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    // ncdu writes names as raw bytes, which JSON parsers reject unless valid UTF-8
+    let text = String::from_utf8_lossy(&bytes);
+    let value: Value = serde_json::from_str(&text).context("Not a valid JSON document")?;
+
+    let top = value
+        .as_array()
+        .filter(|top| top.len() >= 4)
+        .ok_or_else(|| anyhow!("Expected [major version, minor version, metadata, root]"))?;
+    if top[0].as_u64() != Some(MAJOR_VERSION) {
+        bail!("Unsupported export format version {}", top[0]);
+    }
+    let (root_info, root_children) = match &top[3] {
+        Value::Array(items) if !items.is_empty() => (parse_info(&items[0])?, &items[1..]),
+        _ => bail!("The root of the export must be a directory"),
+    };
+
+    let mut tree = DirTree::new(PathBuf::from(&root_info.name));
+    let root = tree.root();
+    apply_info(&mut tree, root, &root_info);
+    let mut links = Vec::new();
+    import_children(&mut tree, root, root_children, root_info.dev, &mut links)?;
+
+    // Without a recorded link count, assume every link is inside the export
+    let mut found: HashMap<FileId, u64> = HashMap::new();
+    for (_, file_id, _) in &links {
+        *found.entry(*file_id).or_default() += 1;
+    }
+    for (node, file_id, link_count) in links {
+        tree.push_hard_link(node, file_id, link_count.unwrap_or(found[&file_id]));
+    }
+
+    tree.finalize();
+    tree.resolve_hard_links();
+    Ok(tree)
+}
+
+/// Adds every item of `items` below `parent`, recursing into directories
+fn import_children(
+    tree: &mut DirTree,
+    parent: NodeId,
+    items: &[Value],
+    device: Option<u64>,
+    links: &mut Vec<(NodeId, FileId, Option<u64>)>,
+) -> Result<()> {
+    for item in items {
+        let (info, children, is_directory) = match item {
+            Value::Array(items) if !items.is_empty() => (parse_info(&items[0])?, &items[1..], true),
+            Value::Object(_) => (parse_info(item)?, &[][..], false),
+            _ => bail!(
+                "Unexpected item below '{}': {}",
+                tree.path(parent).display(),
+                item
+            ),
+        };
+        if info.excluded.is_some() {
+            continue;
+        }
+
+        let size = if is_directory { 0 } else { info.asize };
+        let id = tree.push_child(parent, info.name.clone().into(), is_directory, size);
+        apply_info(tree, id, &info);
+
+        let device = info.dev.or(device);
+        if info.hlnkc && !is_directory {
+            if let (Some(device), Some(inode)) = (device, info.ino) {
+                links.push((id, FileId { device, inode }, info.nlink));
+            }
+        }
+        if is_directory {
+            import_children(tree, id, children, device, links)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_info(value: &Value) -> Result<ItemInfo> {
+    ItemInfo::deserialize(value).with_context(|| format!("Invalid item {}", value))
+}
+
+/// Copies the per-item fields of `info` that `push_child` does not set
+fn apply_info(tree: &mut DirTree, id: NodeId, info: &ItemInfo) {
+    let path = tree.path(id);
+    let node = tree.node_mut(id);
+    node.allocated_bytes = info.dsize;
+    node.modified = info
+        .mtime
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+    if info.read_error {
+        node.errors.push(format!(
+            "{}: could not be read when the export was made",
+            path.display()
+        ));
+    }
+}
+
+fn unix_secs(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since_epoch| since_epoch.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NCDU_EXPORT: &str = r#"[1,2,{"progname":"ncdu","progver":"1.19","timestamp":1700000000},
+[{"name":"/srv","asize":4096,"dsize":4096,"dev":2049,"ino":2},
+ {"name":"notes.txt","asize":120,"dsize":4096,"ino":10,"mtime":1600000000},
+ [{"name":"logs","asize":4096,"dsize":4096,"ino":11,"read_error":true},
+  {"name":"app.log","asize":5000,"dsize":8192,"ino":12,"hlnkc":true},
+  {"name":"app.log.1","asize":5000,"dsize":8192,"ino":12,"hlnkc":true}],
+ [{"name":"mnt","excluded":"otherfs"}],
+ {"name":"fifo","notreg":true,"uid":0,"mode":4480}
+]]"#;
+
+    fn find(tree: &DirTree, name: &str) -> NodeId {
+        let mut stack = vec![tree.root()];
+        while let Some(id) = stack.pop() {
+            if tree.node(id).name == name {
+                return id;
+            }
+            stack.extend(tree.children(id));
+        }
+        panic!("{} not found", name);
+    }
+
+    #[test]
+    fn test_read_ncdu_export() {
+        let tree = read_ncdu(NCDU_EXPORT.as_bytes()).unwrap();
+        let root = tree.node(tree.root());
+
+        assert_eq!(tree.path(tree.root()), PathBuf::from("/srv"));
+        // The two links to inode 12 count once; directories add no apparent size
+        assert_eq!(root.size_bytes, 5120);
+        assert_eq!(root.allocated_bytes, 4096 + 4096 + 4096 + 8192);
+        assert_eq!(root.file_count, 4);
+        assert!(root.partial);
+        assert_eq!(tree.children(tree.root()).len(), 3);
+
+        let logs = find(&tree, "logs");
+        assert_eq!(tree.path(logs), PathBuf::from("/srv/logs"));
+        assert_eq!(tree.node(logs).shared_bytes, 0);
+        assert_eq!(tree.errors().count(), 1);
+        assert_eq!(
+            tree.node(find(&tree, "notes.txt")).modified,
+            Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tree = read_ncdu(NCDU_EXPORT.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        write_ncdu(&mut output, &tree).unwrap();
+
+        let json: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json[0], 1);
+        assert_eq!(json[2]["progname"], "disk-cleaner-rs");
+        assert_eq!(json[3][0]["name"], "/srv");
+
+        let again = read_ncdu(output.as_slice()).unwrap();
+        let (before, after) = (tree.node(tree.root()), again.node(again.root()));
+        assert_eq!(after.size_bytes, before.size_bytes);
+        assert_eq!(after.allocated_bytes, before.allocated_bytes);
+        assert_eq!(after.file_count, before.file_count);
+        assert_eq!(after.modified, before.modified);
+        assert_eq!(again.errors().count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_exports_are_rejected() {
+        assert!(read_ncdu(&b"{}"[..]).is_err());
+        assert!(read_ncdu(&b"[2,0,{},[{\"name\":\"/\"}]]"[..]).is_err());
+        assert!(read_ncdu(&b"[1,0,{},{\"name\":\"/\"}]"[..]).is_err());
+        assert!(read_ncdu(&b"[1,0,{},[{\"name\":\"/\"},42]]"[..]).is_err());
+    }
+}
@@ -26,17 +26,45 @@
 //! ```rust
 //! use platform::PlatformUtils;
 //!
-//! // Check if we can delete a file
-//! if PlatformUtils::can_delete(&path) {
-//!     PlatformUtils::safe_delete(&path, is_directory)?;
-//! } else {
-//!     println!("Permission denied for: {}", path.display());
+//! // Checks existence, protection and permissions, then deletes
+//! if let Err(e) = PlatformUtils::safe_delete(&path, is_directory, &protected) {
+//!     println!("Cannot delete {}: {}", path.display(), e);
 //! }
 //! ```
 
-use anyhow::Result;
+use crate::access::{self, Removal};
+use crate::error::DeleteError;
+use crate::protected::ProtectedPaths;
+use crate::report::EntryKind;
+use crate::trash::{Trash, TrashedEntry};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+
+/// Identity of a file on disk, independent of the path it was reached through.
+///
+/// Two directory entries with the same `FileId` are hard links to the same data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileId {
+    /// Device the file lives on
+    pub device: u64,
+    /// Inode number on that device
+    pub inode: u64,
+}
+
+/// State of a directory's listing at one point in time.
+///
+/// Adding, removing or renaming an entry updates a directory's modification and
+/// change times, so two equal stamps of the same directory mean the same names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirStamp {
+    pub file_id: FileId,
+    /// Modification time as seconds and nanoseconds since the Unix epoch
+    pub modified: (i64, i64),
+    /// Status change time as seconds and nanoseconds since the Unix epoch
+    pub changed: (i64, i64),
+}
 
 /// Cross-platform file operations utility with comprehensive platform support.
 ///
@@ -63,12 +91,12 @@ use std::path::Path;
 ///
 /// ```rust
 /// // Check deletion permissions
-/// if PlatformUtils::can_delete(&file_path) {
+/// if PlatformUtils::check_removable(&file_path, Removal::Delete, &protected).is_ok() {
 ///     println!("File can be safely deleted");
 /// }
 ///
 /// // Perform safe deletion with automatic attribute handling
-/// match PlatformUtils::safe_delete(&file_path, false) {
+/// match PlatformUtils::safe_delete(&file_path, false, &protected) {
 ///     Ok(()) => println!("File deleted successfully"),
 ///     Err(e) => println!("Deletion failed: {}", e),
 /// }
@@ -76,66 +104,70 @@ use std::path::Path;
 pub struct PlatformUtils;
 
 impl PlatformUtils {
-    /// Check if we have write permissions to a path (cross-platform)
-    pub fn can_delete<P: AsRef<Path>>(path: P) -> bool {
-        let path = path.as_ref();
+    /// Refuses `path` if it is or contains a path in `protected`
+    pub fn check_protected(path: &Path, protected: &ProtectedPaths) -> Result<(), DeleteError> {
+        match protected.protection(path) {
+            Some(protection) => Err(DeleteError::Protected {
+                path: path.to_path_buf(),
+                protection,
+            }),
+            None => Ok(()),
+        }
+    }
 
+    /// Checks shared by every way of removing an entry, also used on their own
+    /// to predict the outcome of a dry run
+    ///
+    /// See [`access`] for the permission rules applied on each platform.
+    pub fn check_removable(
+        path: &Path,
+        removal: Removal,
+        protected: &ProtectedPaths,
+    ) -> Result<(), DeleteError> {
         if !path.exists() {
-            return false;
+            return Err(DeleteError::NotFound {
+                path: path.to_path_buf(),
+            });
         }
 
-        // On Windows, check if the file/directory is read-only
-        #[cfg(windows)]
-        {
-            if let Ok(metadata) = fs::metadata(path) {
-                use std::os::windows::fs::MetadataExt;
-                let attributes = metadata.file_attributes();
-                const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
-                return (attributes & FILE_ATTRIBUTE_READONLY) == 0;
-            }
-            false
-        }
+        // Nothing protected is ever removed, whoever asks
+        Self::check_protected(path, protected)?;
 
-        // On Unix-like systems, check if we have write permission
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            if let Ok(metadata) = fs::metadata(path) {
-                let mode = metadata.permissions().mode();
-                // Check if owner has write permission (simplified)
-                return (mode & 0o200) != 0;
-            }
-            false
+        // Check permissions before attempting deletion
+        if let Some(denial) = access::denial(path, removal) {
+            return Err(DeleteError::PermissionDenied {
+                path: path.to_path_buf(),
+                denial,
+            });
         }
 
-        // Fallback for other platforms
-        #[cfg(not(any(windows, unix)))]
-        {
-            // Just try to open the parent directory for writing as a test
-            if path.is_file() {
-                if let Some(parent) = path.parent() {
-                    return parent.exists();
-                }
-            }
-            path.exists()
-        }
+        Ok(())
     }
 
-    /// Safely delete a file or directory with proper error handling
-    pub fn safe_delete<P: AsRef<Path>>(path: P, is_directory: bool) -> Result<()> {
+    /// Move a file or directory into the trash instead of deleting it
+    pub fn safe_trash<P: AsRef<Path>>(
+        path: P,
+        trash: &Trash,
+        protected: &ProtectedPaths,
+    ) -> Result<TrashedEntry, DeleteError> {
         let path = path.as_ref();
+        Self::check_removable(path, Removal::Move, protected)?;
+        trash
+            .move_to_trash(path)
+            .map_err(|source| DeleteError::Trash {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
 
-        if !path.exists() {
-            return Err(anyhow::anyhow!("Path '{}' does not exist", path.display()));
-        }
-
-        // Check permissions before attempting deletion
-        if !Self::can_delete(path) {
-            return Err(anyhow::anyhow!(
-                "Insufficient permissions to delete '{}'",
-                path.display()
-            ));
-        }
+    /// Safely delete a file or directory with proper error handling
+    pub fn safe_delete<P: AsRef<Path>>(
+        path: P,
+        is_directory: bool,
+        protected: &ProtectedPaths,
+    ) -> Result<(), DeleteError> {
+        let path = path.as_ref();
+        Self::check_removable(path, Removal::Delete, protected)?;
 
         // On Windows, remove read-only attribute if present
         #[cfg(windows)]
@@ -157,17 +189,195 @@ impl PlatformUtils {
         }
 
         // Perform the actual deletion
-        if is_directory {
-            fs::remove_dir_all(path).map_err(|e| {
-                anyhow::anyhow!("Failed to delete directory '{}': {}", path.display(), e)
-            })?;
+        let result = if is_directory {
+            fs::remove_dir_all(path)
         } else {
-            fs::remove_file(path).map_err(|e| {
-                anyhow::anyhow!("Failed to delete file '{}': {}", path.display(), e)
-            })?;
+            fs::remove_file(path)
+        };
+        result.map_err(|source| DeleteError::Io {
+            path: path.to_path_buf(),
+            kind: EntryKind::from_is_directory(is_directory),
+            source,
+        })
+    }
+
+    /// Space allocated on disk for an entry, as opposed to its apparent length.
+    ///
+    /// On Unix this is `st_blocks * 512`, which is smaller than the length for
+    /// sparse or filesystem-compressed files and includes a directory's own blocks.
+    /// Other platforms fall back to the apparent length.
+    pub fn allocated_size(metadata: &fs::Metadata) -> u64 {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            metadata.blocks().saturating_mul(512)
         }
 
-        Ok(())
+        #[cfg(not(unix))]
+        {
+            metadata.len()
+        }
+    }
+
+    /// Device and inode of an entry, for detecting hard links.
+    ///
+    /// Returns `None` on platforms without stable inode numbers.
+    pub fn file_id(metadata: &fs::Metadata) -> Option<FileId> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Some(FileId {
+                device: metadata.dev(),
+                inode: metadata.ino(),
+            })
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            None
+        }
+    }
+
+    /// Identity and timestamps of a directory, for noticing changes to its listing.
+    ///
+    /// Returns `None` on platforms without inode numbers and change times.
+    pub fn dir_stamp(metadata: &fs::Metadata) -> Option<DirStamp> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Some(DirStamp {
+                file_id: Self::file_id(metadata)?,
+                modified: (metadata.mtime(), metadata.mtime_nsec()),
+                changed: (metadata.ctime(), metadata.ctime_nsec()),
+            })
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            None
+        }
+    }
+
+    /// Number of hard links pointing at an entry's data (1 where unsupported).
+    pub fn link_count(metadata: &fs::Metadata) -> u64 {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            metadata.nlink()
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            1
+        }
+    }
+
+    /// Raw bytes of a path, for formats that must not lose non-UTF-8 names.
+    ///
+    /// Unix paths are arbitrary bytes and are returned unchanged; elsewhere the
+    /// path is converted to UTF-8, replacing what cannot be represented.
+    pub fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Cow::Borrowed(path.as_os_str().as_bytes())
+        }
+
+        #[cfg(not(unix))]
+        {
+            match path.to_string_lossy() {
+                Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+                Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+            }
+        }
+    }
+
+    /// Path made of the raw bytes `bytes`, the inverse of [`PlatformUtils::path_bytes`].
+    pub fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            PathBuf::from(std::ffi::OsString::from_vec(bytes))
+        }
+
+        #[cfg(not(unix))]
+        {
+            PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+        }
+    }
+
+    /// Canonical path of the entry at `path` itself, for comparing it with others.
+    ///
+    /// The directories leading to it are resolved; a final symbolic link is not
+    /// followed, since removing it only removes the link.
+    pub fn canonical_entry(path: &Path) -> Option<PathBuf> {
+        let parent = match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => return fs::canonicalize(path).ok(),
+        };
+        match path.components().next_back() {
+            Some(Component::Normal(name)) => Some(fs::canonicalize(parent).ok()?.join(name)),
+            _ => fs::canonicalize(path).ok(),
+        }
+    }
+
+    /// Free bytes on the filesystem that contains `path`, as reported by the OS.
+    ///
+    /// Uses `statvfs` on Unix and counts all free blocks, including those reserved
+    /// for root, so that before/after differences reflect exactly what was released.
+    /// Returns `None` where the query is unsupported or fails.
+    pub fn free_space<P: AsRef<Path>>(path: P) -> Option<u64> {
+        #[cfg(unix)]
+        {
+            use std::ffi::CString;
+            use std::os::unix::ffi::OsStrExt;
+
+            let c_path = CString::new(path.as_ref().as_os_str().as_bytes()).ok()?;
+            let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+            // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a
+            // properly sized, writable statvfs buffer
+            if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+                return None;
+            }
+            // Field widths differ between platforms
+            #[allow(clippy::unnecessary_cast)]
+            Some((stat.f_bfree as u64).saturating_mul(stat.f_frsize as u64))
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            None
+        }
+    }
+
+    /// Format seconds since the Unix epoch as local time, `YYYY-MM-DDThh:mm:ss`
+    pub fn local_time(unix_secs: u64) -> String {
+        #[cfg(unix)]
+        {
+            let time = unix_secs as libc::time_t;
+            let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+            // SAFETY: `localtime_r` reads `time` and writes only into the
+            // zero-initialised `tm` we own
+            if !unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+                return format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    tm.tm_year + 1900,
+                    tm.tm_mon + 1,
+                    tm.tm_mday,
+                    tm.tm_hour,
+                    tm.tm_min,
+                    tm.tm_sec
+                );
+            }
+        }
+
+        let _ = unix_secs;
+        "1970-01-01T00:00:00".to_string()
     }
 
     /// Get a user-friendly error message for common file operation errors
@@ -190,24 +400,60 @@ impl PlatformUtils {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protected::ProtectedKind;
+    use anyhow::Result;
     use std::fs::File;
     use tempfile::TempDir;
 
     #[test]
-    fn test_can_delete_existing_file() {
+    fn test_check_removable_existing_file() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
         File::create(&file_path).unwrap();
 
-        assert!(PlatformUtils::can_delete(&file_path));
+        assert!(PlatformUtils::check_removable(
+            &file_path,
+            Removal::Delete,
+            &ProtectedPaths::new()
+        )
+        .is_ok());
     }
 
     #[test]
-    fn test_can_delete_nonexistent_file() {
+    fn test_check_removable_nonexistent_file() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("nonexistent.txt");
 
-        assert!(!PlatformUtils::can_delete(&file_path));
+        assert!(matches!(
+            PlatformUtils::check_removable(&file_path, Removal::Delete, &ProtectedPaths::new()),
+            Err(DeleteError::NotFound { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_safe_delete_refuses_denied_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // The superuser may remove anything, so there is nothing to refuse
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().join("locked");
+        fs::create_dir(&dir_path).unwrap();
+        let file_path = dir_path.join("test.txt");
+        File::create(&file_path).unwrap();
+        fs::set_permissions(&dir_path, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let error =
+            PlatformUtils::safe_delete(&file_path, false, &ProtectedPaths::new()).unwrap_err();
+        assert!(matches!(error, DeleteError::PermissionDenied { .. }));
+        let error =
+            PlatformUtils::safe_delete(&dir_path, true, &ProtectedPaths::new()).unwrap_err();
+        assert!(matches!(error, DeleteError::PermissionDenied { .. }));
+        assert!(file_path.exists());
+        fs::set_permissions(&dir_path, fs::Permissions::from_mode(0o755)).unwrap();
     }
 
     #[test]
@@ -217,7 +463,7 @@ mod tests {
         File::create(&file_path).unwrap();
 
         assert!(file_path.exists());
-        PlatformUtils::safe_delete(&file_path, false)?;
+        PlatformUtils::safe_delete(&file_path, false, &ProtectedPaths::new())?;
         assert!(!file_path.exists());
 
         Ok(())
@@ -230,12 +476,108 @@ mod tests {
         fs::create_dir(&dir_path).unwrap();
 
         assert!(dir_path.exists());
-        PlatformUtils::safe_delete(&dir_path, true)?;
+        PlatformUtils::safe_delete(&dir_path, true, &ProtectedPaths::new())?;
         assert!(!dir_path.exists());
 
         Ok(())
     }
 
+    #[test]
+    fn test_safe_delete_refuses_protected_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().join("keep");
+        fs::create_dir(&dir_path).unwrap();
+        File::create(dir_path.join("data.txt")).unwrap();
+        let mut protected = ProtectedPaths::new();
+        protected.protect(&dir_path, ProtectedKind::Configured);
+
+        for path in [&dir_path, &temp_dir.path().to_path_buf()] {
+            let error = PlatformUtils::safe_delete(path, true, &protected).unwrap_err();
+            assert!(matches!(error, DeleteError::Protected { .. }));
+            assert_eq!(error.io_kind(), std::io::ErrorKind::PermissionDenied);
+        }
+        assert!(dir_path.join("data.txt").exists());
+        // Only what it contains may still go
+        PlatformUtils::safe_delete(dir_path.join("data.txt"), false, &protected).unwrap();
+
+        // Checked without deleting anything, whatever happens
+        let root = Path::new("/");
+        assert!(matches!(
+            PlatformUtils::check_removable(root, Removal::Delete, &protected),
+            Err(DeleteError::Protected { .. })
+        ));
+        let program = std::env::current_exe().unwrap();
+        assert!(
+            PlatformUtils::check_removable(&program, Removal::Delete, &protected)
+                .unwrap_err()
+                .to_string()
+                .ends_with("it is the running program")
+        );
+    }
+
+    #[test]
+    fn test_allocated_size_of_regular_file() {
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("data.bin");
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(&[b'x'; 10_000]).unwrap();
+        file.sync_all().unwrap();
+
+        let metadata = fs::metadata(&file_path).unwrap();
+        // Allocation is done in whole blocks, so a written file takes at least its length
+        assert!(PlatformUtils::allocated_size(&metadata) >= 10_000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allocated_size_of_sparse_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("sparse.img");
+        let file = File::create(&file_path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+
+        let metadata = fs::metadata(&file_path).unwrap();
+        assert_eq!(metadata.len(), 64 * 1024 * 1024);
+        assert!(PlatformUtils::allocated_size(&metadata) < metadata.len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_id_identifies_hard_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("original.txt");
+        let link = temp_dir.path().join("link.txt");
+        let other = temp_dir.path().join("other.txt");
+        File::create(&original).unwrap();
+        File::create(&other).unwrap();
+        fs::hard_link(&original, &link).unwrap();
+
+        let original_meta = fs::metadata(&original).unwrap();
+        let link_meta = fs::metadata(&link).unwrap();
+        let other_meta = fs::metadata(&other).unwrap();
+
+        assert_eq!(
+            PlatformUtils::file_id(&original_meta),
+            PlatformUtils::file_id(&link_meta)
+        );
+        assert_ne!(
+            PlatformUtils::file_id(&original_meta),
+            PlatformUtils::file_id(&other_meta)
+        );
+        assert_eq!(PlatformUtils::link_count(&link_meta), 2);
+        assert_eq!(PlatformUtils::link_count(&other_meta), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_free_space_of_existing_path() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(PlatformUtils::free_space(temp_dir.path()).is_some());
+        assert!(PlatformUtils::free_space(temp_dir.path().join("missing")).is_none());
+    }
+
     #[test]
     fn test_friendly_error_messages() {
         let perm_error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "test");
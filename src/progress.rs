@@ -0,0 +1,243 @@
+//! # Scan Progress
+//!
+//! Counters that the walker updates while it runs, and a live status line that
+//! shows them on stderr so long scans do not look stuck.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! let progress = Arc::new(ScanProgress::default());
+//! let analyzer = DiskAnalyzer::new(1).with_progress(progress.clone());
+//! let status = StatusLine::start(progress);
+//! let tree = analyzer.analyze_tree("/srv").await?;
+//! status.finish().await;
+//! ```
+
+use humansize::{format_size, DECIMAL};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How often the status line is redrawn
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Running totals of a scan, shared between the walker threads and whoever
+/// displays them.
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    files: AtomicU64,
+    bytes: AtomicU64,
+    directories_pending: AtomicU64,
+    errors: AtomicU64,
+    /// A directory that is being read right now
+    current: Mutex<PathBuf>,
+}
+
+/// The counters of a [`ScanProgress`] at one moment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Files (and other non-directories) seen so far
+    pub files: u64,
+    /// Apparent bytes of those files
+    pub bytes: u64,
+    /// Directories found but not yet read
+    pub directories_pending: u64,
+    /// Entries that could not be read
+    pub errors: u64,
+    /// A directory that was being read
+    pub current: PathBuf,
+}
+
+impl ScanProgress {
+    /// `count` more directories were found and wait to be read
+    pub fn directories_found(&self, count: u64) {
+        self.directories_pending.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Reading the directory `path` started
+    pub fn directory_started(&self, path: &Path) {
+        // Skipped when another thread is updating it; any current directory will do
+        if let Ok(mut current) = self.current.try_lock() {
+            current.clear();
+            current.push(path);
+        }
+    }
+
+    /// A directory was read, holding `files` files of `bytes` bytes, with `errors` errors
+    pub fn directory_finished(&self, files: u64, bytes: u64, errors: u64) {
+        self.record(files, bytes, errors);
+        self.directories_pending.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// `files` files of `bytes` bytes and `errors` errors were seen outside any directory
+    pub fn record(&self, files: u64, bytes: u64, errors: u64) {
+        self.files.fetch_add(files, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.errors.fetch_add(errors, Ordering::Relaxed);
+    }
+
+    /// The current counters
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        ProgressSnapshot {
+            files: self.files.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            directories_pending: self.directories_pending.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            current: self
+                .current
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+}
+
+/// A status line on stderr, redrawn from a [`ScanProgress`] until finished.
+#[derive(Debug)]
+pub struct StatusLine {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl StatusLine {
+    /// Starts redrawing the status line for `progress`
+    pub fn start(progress: Arc<ScanProgress>) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let started = Instant::now();
+            let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        // Some terminals report no size at all
+                        let width = crossterm::terminal::size()
+                            .ok()
+                            .filter(|&(columns, _)| columns > 0)
+                            .map_or(80, |(columns, _)| columns);
+                        let text = status_text(&progress.snapshot(), started.elapsed(), width.into());
+                        let mut stderr = std::io::stderr().lock();
+                        let _ = write!(stderr, "\r\x1b[2K{}", text);
+                        let _ = stderr.flush();
+                    }
+                    _ = &mut stopped => break,
+                }
+            }
+            let _ = write!(std::io::stderr(), "\r\x1b[2K");
+        });
+        Self { stop, task }
+    }
+
+    /// Stops redrawing and clears the line
+    pub async fn finish(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// One line describing `snapshot`, taken `elapsed` after the scan started, cut to
+/// `width` terminal columns by shortening the path from the left
+pub fn status_text(snapshot: &ProgressSnapshot, elapsed: Duration, width: usize) -> String {
+    let seconds = elapsed.as_secs_f64().max(0.001);
+    let mut text = format!(
+        "⏳ {} files ({:.0}/s), {} ({}/s), {} dirs pending",
+        snapshot.files,
+        snapshot.files as f64 / seconds,
+        format_size(snapshot.bytes, DECIMAL),
+        format_size((snapshot.bytes as f64 / seconds) as u64, DECIMAL),
+        snapshot.directories_pending
+    );
+    if snapshot.errors > 0 {
+        text.push_str(&format!(", {} errors", snapshot.errors));
+    }
+
+    let path = snapshot.current.to_string_lossy();
+    let room = width.saturating_sub(text.width() + 3);
+    if room > 1 && !path.is_empty() {
+        text.push_str(" · ");
+        if path.width() <= room {
+            text.push_str(&path);
+        } else {
+            text.push('…');
+            let tail = fit_width(path.chars().rev(), room - 1);
+            text.extend(tail.chars().rev());
+        }
+    }
+    fit_width(text.chars(), width)
+}
+
+/// The leading `chars` that take up at most `width` terminal columns
+fn fit_width(chars: impl Iterator<Item = char>, width: usize) -> String {
+    let mut used = 0;
+    chars
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let progress = ScanProgress::default();
+        progress.directories_found(3);
+        progress.directory_started(Path::new("/data/a"));
+        progress.directory_finished(10, 5000, 1);
+        progress.record(1, 100, 0);
+
+        assert_eq!(
+            progress.snapshot(),
+            ProgressSnapshot {
+                files: 11,
+                bytes: 5100,
+                directories_pending: 2,
+                errors: 1,
+                current: PathBuf::from("/data/a"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_status_text_fits_width() {
+        let snapshot = ProgressSnapshot {
+            files: 2000,
+            bytes: 4_000_000,
+            directories_pending: 7,
+            errors: 0,
+            current: PathBuf::from("/srv/data/projects/very/deep/directory"),
+        };
+        let text = status_text(&snapshot, Duration::from_secs(2), 200);
+        assert_eq!(
+            text,
+            "⏳ 2000 files (1000/s), 4 MB (2 MB/s), 7 dirs pending · \
+             /srv/data/projects/very/deep/directory"
+        );
+
+        // The path is cut from the left, keeping the part that changes
+        // Measured in columns: the hourglass takes two
+        let text = status_text(&snapshot, Duration::from_secs(2), 70);
+        assert_eq!(text.width(), 70);
+        assert_eq!(text.chars().count(), 69);
+        assert!(text.ends_with(" · …eep/directory"));
+
+        let errors = ProgressSnapshot {
+            errors: 3,
+            ..snapshot
+        };
+        for width in [1, 2, 20, 61] {
+            assert!(status_text(&errors, Duration::from_secs(2), width).width() <= width);
+        }
+        assert!(status_text(&errors, Duration::from_secs(2), 200).contains(", 3 errors"));
+    }
+}
@@ -0,0 +1,438 @@
+//! # Protected Paths
+//!
+//! A deny-list consulted before anything is deleted or trashed, so that no
+//! selection, rules file or stray `..` can take the system down with it. An entry
+//! is refused if it is a protected path or contains one.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Built In
+//!
+//! - The filesystem root
+//! - Home directories: `$HOME`, `/home`, `/Users` and every directory directly in them
+//! - System directories such as `/etc`, `/usr` or `C:\Windows`
+//! - The current working directory and the root of the git work tree around it
+//! - The running program
+//!
+//! More paths are added to a [`ProtectedPaths`] with `--protect` or
+//! `DISK_CLEANER_PROTECT`, and the scanned directory is protected for the duration
+//! of a run.
+
+use crate::git;
+use crate::platform::PlatformUtils;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// System directories on Unix-like systems; those missing here are skipped
+#[cfg(unix)]
+const SYSTEM_DIRECTORIES: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/root",
+    "/run",
+    "/sbin",
+    "/srv",
+    "/sys",
+    "/tmp",
+    "/usr",
+    "/usr/bin",
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local",
+    "/usr/sbin",
+    "/usr/share",
+    "/var",
+    "/var/lib",
+    "/var/log",
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Volumes",
+    "/private",
+];
+
+/// Environment variables naming system directories on Windows
+#[cfg(windows)]
+const SYSTEM_DIRECTORY_VARIABLES: &[&str] = &[
+    "SystemRoot",
+    "ProgramFiles",
+    "ProgramFiles(x86)",
+    "ProgramData",
+];
+
+/// Directories holding home directories; both they and their direct children are protected
+#[cfg(not(windows))]
+const HOME_PARENTS: &[&str] = &["/home", "/Users", "/var/home"];
+#[cfg(windows)]
+const HOME_PARENTS: &[&str] = &["C:\\Users"];
+
+/// Why a path is protected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectedKind {
+    Root,
+    Home,
+    System,
+    WorkingDirectory,
+    Repository,
+    Program,
+    ScanRoot,
+    Configured,
+    /// Not known to be protected, but where it leads could not be worked out
+    Unresolved,
+}
+
+impl ProtectedKind {
+    fn describe(self) -> &'static str {
+        match self {
+            ProtectedKind::Root => "the filesystem root",
+            ProtectedKind::Home => "a home directory",
+            ProtectedKind::System => "a system directory",
+            ProtectedKind::WorkingDirectory => "the current working directory",
+            ProtectedKind::Repository => "the current git repository",
+            ProtectedKind::Program => "the running program",
+            ProtectedKind::ScanRoot => "the scanned directory",
+            ProtectedKind::Configured => "a protected path",
+            ProtectedKind::Unresolved => "a path that cannot be resolved",
+        }
+    }
+}
+
+/// A protected path that deleting some entry would remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Protection {
+    pub kind: ProtectedKind,
+    /// The protected path itself
+    pub path: PathBuf,
+    /// Whether the entry only contains the protected path, rather than being it
+    pub contained: bool,
+}
+
+impl fmt::Display for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.contained {
+            write!(
+                f,
+                "it contains {} '{}'",
+                self.kind.describe(),
+                self.path.display()
+            )
+        } else {
+            write!(f, "it is {}", self.kind.describe())
+        }
+    }
+}
+
+/// Paths protected by default, worked out once per run
+struct BuiltIn {
+    paths: Vec<(PathBuf, ProtectedKind)>,
+    home_parents: Vec<PathBuf>,
+}
+
+static BUILT_IN: OnceLock<BuiltIn> = OnceLock::new();
+
+impl BuiltIn {
+    fn load() -> Self {
+        Self::load_with_home(env::var_os(if cfg!(windows) {
+            "USERPROFILE"
+        } else {
+            "HOME"
+        }))
+    }
+
+    /// Built-in paths for the home directory `home`
+    fn load_with_home(home: Option<OsString>) -> Self {
+        let mut candidates: Vec<(PathBuf, ProtectedKind)> = Vec::new();
+
+        #[cfg(unix)]
+        candidates.extend(
+            SYSTEM_DIRECTORIES
+                .iter()
+                .map(|dir| (PathBuf::from(dir), ProtectedKind::System)),
+        );
+        #[cfg(windows)]
+        candidates.extend(
+            SYSTEM_DIRECTORY_VARIABLES
+                .iter()
+                .filter_map(env::var_os)
+                .map(|dir| (PathBuf::from(dir), ProtectedKind::System)),
+        );
+
+        candidates.extend(home.map(|home| (PathBuf::from(home), ProtectedKind::Home)));
+        if let Ok(current) = env::current_dir() {
+            if let Some(repository) = git::work_tree_root(&current) {
+                candidates.push((repository, ProtectedKind::Repository));
+            }
+            candidates.push((current, ProtectedKind::WorkingDirectory));
+        }
+        if let Ok(program) = env::current_exe() {
+            candidates.push((program, ProtectedKind::Program));
+        }
+
+        let home_parents: Vec<PathBuf> = HOME_PARENTS
+            .iter()
+            .filter_map(|dir| fs::canonicalize(dir).ok())
+            .collect();
+        candidates.extend(
+            HOME_PARENTS
+                .iter()
+                .map(|dir| (PathBuf::from(dir), ProtectedKind::Home)),
+        );
+
+        Self {
+            paths: candidates
+                .into_iter()
+                .flat_map(|(path, kind)| {
+                    Self::forms(&path).into_iter().map(move |form| (form, kind))
+                })
+                .collect(),
+            home_parents,
+        }
+    }
+
+    /// `path` as written and with every symbolic link resolved.
+    ///
+    /// Entries are matched without following their last component, so a protected
+    /// symbolic link such as `/bin -> usr/bin` has to be kept under both names.
+    fn forms(path: &Path) -> Vec<PathBuf> {
+        let mut forms: Vec<PathBuf> = PlatformUtils::canonical_entry(path)
+            .filter(|entry| entry.symlink_metadata().is_ok())
+            .into_iter()
+            .collect();
+        if let Ok(resolved) = fs::canonicalize(path) {
+            if !forms.contains(&resolved) {
+                forms.push(resolved);
+            }
+        }
+        forms
+    }
+
+    /// What [`ProtectedPaths::protection`] finds with these built-in paths and `added`
+    fn protection(&self, path: &Path, added: &[(PathBuf, ProtectedKind)]) -> Option<Protection> {
+        let Some(target) = PlatformUtils::canonical_entry(path) else {
+            // Nothing there is nothing to protect; anything else cannot be checked
+            return match path.symlink_metadata() {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                _ => Some(Protection {
+                    kind: ProtectedKind::Unresolved,
+                    path: path.to_path_buf(),
+                    contained: false,
+                }),
+            };
+        };
+        let Some(parent) = target.parent() else {
+            return Some(Protection {
+                kind: ProtectedKind::Root,
+                path: target,
+                contained: false,
+            });
+        };
+
+        if self.home_parents.iter().any(|dir| dir == parent) {
+            return Some(Protection {
+                kind: ProtectedKind::Home,
+                path: target,
+                contained: false,
+            });
+        }
+
+        self.paths
+            .iter()
+            .chain(added)
+            .filter(|(protected, _)| protected.starts_with(&target))
+            .min_by_key(|(protected, _)| protected.components().count())
+            .map(|(protected, kind)| Protection {
+                kind: *kind,
+                path: protected.clone(),
+                contained: *protected != target,
+            })
+    }
+}
+
+/// The built-in protected paths and those added for one run.
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedPaths {
+    added: Vec<(PathBuf, ProtectedKind)>,
+}
+
+impl ProtectedPaths {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Protects `path` and everything containing it.
+    ///
+    /// A path that does not exist yet is kept as given.
+    pub fn protect(&mut self, path: &Path, kind: ProtectedKind) {
+        let mut forms = BuiltIn::forms(path);
+        if forms.is_empty() {
+            forms.push(path.to_path_buf());
+        }
+        self.added
+            .extend(forms.into_iter().map(|form| (form, kind)));
+    }
+
+    /// The protected path that deleting `path` would remove, if any.
+    ///
+    /// When several are, the one closest to `path` is returned.
+    pub fn protection(&self, path: &Path) -> Option<Protection> {
+        BUILT_IN
+            .get_or_init(BuiltIn::load)
+            .protection(path, &self.added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// What the built-in paths alone protect
+    fn protection(path: &Path) -> Option<Protection> {
+        ProtectedPaths::new().protection(path)
+    }
+
+    #[test]
+    fn test_built_in_protections() {
+        let root = protection(Path::new("/")).unwrap();
+        assert_eq!(root.kind, ProtectedKind::Root);
+        assert_eq!(root.to_string(), "it is the filesystem root");
+
+        let program = env::current_exe().unwrap();
+        let protection = protection(&program).unwrap();
+        assert_eq!(protection.kind, ProtectedKind::Program);
+        assert!(!protection.contained);
+
+        let current = fs::canonicalize(env::current_dir().unwrap()).unwrap();
+        let built_in = BUILT_IN.get_or_init(BuiltIn::load);
+        assert!(built_in
+            .paths
+            .contains(&(current.clone(), ProtectedKind::WorkingDirectory)));
+        assert!(self::protection(&current).is_some());
+        // Containing the working directory is as bad as being it
+        assert!(self::protection(current.parent().unwrap()).is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_and_home_directories() {
+        let etc = protection(Path::new("/etc")).unwrap();
+        assert_eq!(etc.kind, ProtectedKind::System);
+        assert_eq!(etc.to_string(), "it is a system directory");
+
+        let usr = protection(Path::new("/usr")).unwrap();
+        assert_eq!((usr.kind, usr.contained), (ProtectedKind::System, false));
+        // Merged-/usr systems link `/bin` to `/usr/bin`
+        let bin = protection(Path::new("/bin")).unwrap();
+        assert_eq!((bin.kind, bin.contained), (ProtectedKind::System, false));
+
+        if let Some(home) = env::var_os("HOME").and_then(|home| fs::canonicalize(home).ok()) {
+            assert!(protection(&home).is_some());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_home_parents_are_protected_whatever_home_is() {
+        if !Path::new("/home").is_dir() {
+            return;
+        }
+        let home = protection(Path::new("/home")).unwrap();
+        assert_eq!((home.kind, home.contained), (ProtectedKind::Home, false));
+
+        // Root and service accounts often live outside `/home`
+        let built_in = BuiltIn::load_with_home(Some(OsString::from("/nonexistent")));
+        let home = built_in.protection(Path::new("/home"), &[]).unwrap();
+        assert_eq!((home.kind, home.contained), (ProtectedKind::Home, false));
+        assert!(built_in
+            .protection(Path::new("/home/someone"), &[])
+            .is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_paths_protect_link_and_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = fs::canonicalize(temp_dir.path()).unwrap();
+        fs::create_dir_all(base.join("usr/bin")).unwrap();
+        std::os::unix::fs::symlink("usr/bin", base.join("bin")).unwrap();
+
+        // Like `/bin -> usr/bin` on merged-/usr systems
+        let built_in = BuiltIn::load_with_home(Some(base.join("bin").into_os_string()));
+        let link = built_in.protection(&base.join("bin"), &[]).unwrap();
+        assert_eq!((link.kind, link.contained), (ProtectedKind::Home, false));
+        let target = built_in.protection(&base.join("usr/bin"), &[]).unwrap();
+        assert_eq!(
+            (target.kind, target.contained),
+            (ProtectedKind::Home, false)
+        );
+        assert!(
+            built_in
+                .protection(&base.join("usr"), &[])
+                .unwrap()
+                .contained
+        );
+        assert_eq!(built_in.protection(&base.join("usr/bin/tool"), &[]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unresolvable_paths_are_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = fs::canonicalize(temp_dir.path()).unwrap();
+        std::os::unix::fs::symlink("loop", base.join("loop")).unwrap();
+
+        let unresolved = protection(&base.join("loop/entry")).unwrap();
+        assert_eq!(unresolved.kind, ProtectedKind::Unresolved);
+        assert_eq!(
+            unresolved.to_string(),
+            "it is a path that cannot be resolved"
+        );
+        // A path that is not there at all has nothing to protect
+        assert_eq!(protection(&base.join("missing/entry")), None);
+    }
+
+    #[test]
+    fn test_added_paths_protect_themselves_and_their_ancestors() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = fs::canonicalize(temp_dir.path()).unwrap();
+        fs::create_dir_all(base.join("keep/inner")).unwrap();
+        fs::create_dir(base.join("other")).unwrap();
+        let mut protected = ProtectedPaths::new();
+        protected.protect(&base.join("keep"), ProtectedKind::Configured);
+        let protection = |path: &Path| protected.protection(path);
+
+        let keep = protection(&base.join("keep")).unwrap();
+        assert_eq!(
+            (keep.kind, keep.contained),
+            (ProtectedKind::Configured, false)
+        );
+        let above = protection(&base).unwrap();
+        assert!(above.contained);
+        assert_eq!(
+            above.to_string(),
+            format!(
+                "it contains a protected path '{}'",
+                base.join("keep").display()
+            )
+        );
+        assert_eq!(protection(&base.join("keep/inner")), None);
+        assert_eq!(protection(&base.join("other")), None);
+        // Reached through `..`, it is still the same directory
+        assert!(protection(&base.join("other/../keep")).is_some());
+        // Nothing is left behind for other lists
+        assert_eq!(self::protection(&base.join("keep")), None);
+    }
+}
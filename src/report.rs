@@ -0,0 +1,350 @@
+//! # Deletion Reports
+//!
+//! Structured, serializable record of what a deletion run did: one record per
+//! requested entry with its outcome, error kind, bytes released and timing, plus
+//! the free-space change observed on each affected filesystem.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+
+use crate::access::DenyReason;
+use crate::error::DeleteError;
+use crate::serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Whether an entry is a file or a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+impl EntryKind {
+    pub fn from_is_directory(is_directory: bool) -> Self {
+        if is_directory {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryKind::File => f.write_str("file"),
+            EntryKind::Directory => f.write_str("directory"),
+        }
+    }
+}
+
+/// What happened to a single entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionOutcome {
+    /// The entry was removed permanently
+    Deleted,
+    /// The entry was moved into the trash
+    Trashed,
+    /// The entry no longer existed
+    NotFound,
+    /// The entry could not be removed for lack of permissions
+    PermissionDenied,
+    /// The entry could not be removed for any other reason
+    Failed,
+}
+
+impl DeletionOutcome {
+    fn from_error(error: &DeleteError) -> Self {
+        match error.io_kind() {
+            io::ErrorKind::NotFound => DeletionOutcome::NotFound,
+            io::ErrorKind::PermissionDenied => DeletionOutcome::PermissionDenied,
+            _ => DeletionOutcome::Failed,
+        }
+    }
+}
+
+/// Result of deleting one entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeletionRecord {
+    /// Path that was deleted (or that deletion was attempted on)
+    #[serde(serialize_with = "serialize::path")]
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub outcome: DeletionOutcome,
+    /// Underlying I/O error kind when the deletion failed
+    #[serde(serialize_with = "serialize::error_kind")]
+    pub error_kind: Option<io::ErrorKind>,
+    /// Why the entry may not be removed, when permissions refused it
+    pub deny_reason: Option<DenyReason>,
+    /// User-friendly error message when the deletion failed
+    pub error: Option<String>,
+    /// Where the entry now lives in the trash, if it was trashed
+    #[serde(serialize_with = "serialize::optional_path")]
+    pub trashed_to: Option<PathBuf>,
+    /// Allocated bytes the entry held, measured right before removal; 0 on failure.
+    /// For trashed entries they are only freed once the trash is emptied.
+    pub bytes: u64,
+    /// Time spent measuring and removing the entry
+    #[serde(rename = "duration_ms", serialize_with = "serialize::millis")]
+    pub duration: Duration,
+}
+
+impl DeletionRecord {
+    /// Record for an entry that was removed
+    pub fn deleted(path: &Path, kind: EntryKind, bytes: u64, duration: Duration) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            kind,
+            outcome: DeletionOutcome::Deleted,
+            error_kind: None,
+            deny_reason: None,
+            error: None,
+            trashed_to: None,
+            bytes,
+            duration,
+        }
+    }
+
+    /// Record for an entry that was moved to `destination` in the trash (unknown
+    /// in a dry run)
+    pub fn trashed(
+        path: &Path,
+        kind: EntryKind,
+        destination: Option<&Path>,
+        bytes: u64,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            outcome: DeletionOutcome::Trashed,
+            trashed_to: destination.map(Path::to_path_buf),
+            ..Self::deleted(path, kind, bytes, duration)
+        }
+    }
+
+    /// Record for an entry that could not be removed
+    pub fn failed(path: &Path, kind: EntryKind, error: &DeleteError, duration: Duration) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            kind,
+            outcome: DeletionOutcome::from_error(error),
+            error_kind: Some(error.io_kind()),
+            deny_reason: error.deny_reason(),
+            error: Some(error.friendly_message()),
+            trashed_to: None,
+            bytes: 0,
+            duration,
+        }
+    }
+
+    /// Whether the entry was deleted or trashed
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self.outcome,
+            DeletionOutcome::Deleted | DeletionOutcome::Trashed
+        )
+    }
+}
+
+/// Free space on one filesystem before and after a deletion run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FilesystemDelta {
+    /// Directory used to query the filesystem (the parent of a deleted entry)
+    #[serde(serialize_with = "serialize::path")]
+    pub probe: PathBuf,
+    /// Free bytes reported before anything was deleted
+    pub free_before: u64,
+    /// Free bytes reported after all deletions finished
+    pub free_after: u64,
+}
+
+impl FilesystemDelta {
+    /// Growth in free space; 0 if other activity used more than was released
+    pub fn freed_bytes(&self) -> u64 {
+        self.free_after.saturating_sub(self.free_before)
+    }
+}
+
+/// Per-entry records of a deletion run plus the observed free-space changes.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct DeletionReport {
+    /// One record per requested entry, in request order
+    pub entries: Vec<DeletionRecord>,
+    /// One delta per affected filesystem, where the platform can report free space
+    pub filesystems: Vec<FilesystemDelta>,
+    /// Wall-clock time of the whole run
+    #[serde(rename = "duration_ms", serialize_with = "serialize::millis")]
+    pub duration: Duration,
+    /// True if nothing was touched and every outcome is a prediction
+    pub dry_run: bool,
+}
+
+impl DeletionReport {
+    /// Entries that were deleted or trashed
+    pub fn deleted(&self) -> impl Iterator<Item = &DeletionRecord> {
+        self.entries.iter().filter(|r| r.is_success())
+    }
+
+    /// Entries that could not be deleted
+    pub fn failed(&self) -> impl Iterator<Item = &DeletionRecord> {
+        self.entries.iter().filter(|r| !r.is_success())
+    }
+
+    /// Sum of bytes reclaimed by the permanent deletions
+    pub fn bytes_freed(&self) -> u64 {
+        self.bytes_with(DeletionOutcome::Deleted)
+    }
+
+    /// Sum of bytes moved into the trash, freed once it is emptied
+    pub fn bytes_trashed(&self) -> u64 {
+        self.bytes_with(DeletionOutcome::Trashed)
+    }
+
+    fn bytes_with(&self, outcome: DeletionOutcome) -> u64 {
+        self.entries
+            .iter()
+            .filter(|r| r.outcome == outcome)
+            .map(|r| r.bytes)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::Denial;
+
+    fn sample_report() -> DeletionReport {
+        let error = DeleteError::Io {
+            path: PathBuf::from("/data/locked"),
+            kind: EntryKind::Directory,
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        };
+        DeletionReport {
+            entries: vec![
+                DeletionRecord::deleted(
+                    Path::new("/data/old.log"),
+                    EntryKind::File,
+                    4096,
+                    Duration::from_millis(3),
+                ),
+                DeletionRecord::failed(
+                    Path::new("/data/locked"),
+                    EntryKind::Directory,
+                    &error,
+                    Duration::from_millis(1),
+                ),
+                DeletionRecord::trashed(
+                    Path::new("/data/cache"),
+                    EntryKind::Directory,
+                    Some(Path::new("/home/me/.local/share/Trash/files/cache")),
+                    1000,
+                    Duration::from_millis(1),
+                ),
+            ],
+            filesystems: vec![],
+            duration: Duration::from_millis(5),
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_report_outcomes() {
+        let report = sample_report();
+
+        assert_eq!(report.deleted().count(), 2);
+        assert_eq!(report.failed().count(), 1);
+        assert_eq!(report.bytes_freed(), 4096);
+        assert_eq!(report.bytes_trashed(), 1000);
+
+        let failed = report.failed().next().unwrap();
+        assert_eq!(failed.outcome, DeletionOutcome::PermissionDenied);
+        assert_eq!(failed.error_kind, Some(io::ErrorKind::PermissionDenied));
+        assert_eq!(failed.bytes, 0);
+    }
+
+    #[test]
+    fn test_missing_entry_outcome() {
+        let error = DeleteError::NotFound {
+            path: PathBuf::from("/data/gone"),
+        };
+        let record = DeletionRecord::failed(
+            Path::new("/data/gone"),
+            EntryKind::File,
+            &error,
+            Duration::ZERO,
+        );
+        assert_eq!(record.outcome, DeletionOutcome::NotFound);
+        assert!(record.error.unwrap().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_denied_entry_carries_reason() {
+        let error = DeleteError::PermissionDenied {
+            path: PathBuf::from("/tmp/shared"),
+            denial: Denial {
+                reason: DenyReason::StickyNotOwner,
+                path: PathBuf::from("/tmp/shared"),
+            },
+        };
+        let record = DeletionRecord::failed(
+            Path::new("/tmp/shared"),
+            EntryKind::File,
+            &error,
+            Duration::ZERO,
+        );
+        assert_eq!(record.outcome, DeletionOutcome::PermissionDenied);
+        assert!(record
+            .error
+            .as_deref()
+            .unwrap()
+            .ends_with("'/tmp/shared' belongs to another user in a sticky directory"));
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["deny_reason"], "sticky_not_owner");
+    }
+
+    #[test]
+    fn test_report_serializes() {
+        let json = serde_json::to_value(sample_report()).unwrap();
+
+        assert_eq!(json["duration_ms"], 5.0);
+        assert_eq!(json["dry_run"], false);
+        let deleted = &json["entries"][0];
+        assert_eq!(deleted["path"], "/data/old.log");
+        assert_eq!(deleted["kind"], "file");
+        assert_eq!(deleted["outcome"], "deleted");
+        assert_eq!(deleted["error_kind"], serde_json::Value::Null);
+        assert_eq!(deleted["bytes"], 4096);
+        assert_eq!(deleted["trashed_to"], serde_json::Value::Null);
+        assert_eq!(deleted["duration_ms"], 3.0);
+
+        let failed = &json["entries"][1];
+        assert_eq!(failed["kind"], "directory");
+        assert_eq!(failed["outcome"], "permission_denied");
+        assert_eq!(failed["error_kind"], "PermissionDenied");
+        assert_eq!(failed["deny_reason"], serde_json::Value::Null);
+
+        let trashed = &json["entries"][2];
+        assert_eq!(trashed["outcome"], "trashed");
+        assert_eq!(
+            trashed["trashed_to"],
+            "/home/me/.local/share/Trash/files/cache"
+        );
+    }
+
+    #[test]
+    fn test_filesystem_delta_never_negative() {
+        let delta = FilesystemDelta {
+            probe: PathBuf::from("/"),
+            free_before: 1000,
+            free_after: 400,
+        };
+        assert_eq!(delta.freed_bytes(), 0);
+    }
+}
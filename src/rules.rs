@@ -0,0 +1,430 @@
+//! # Cleanup Rules
+//!
+//! Declarative selection of entries for unattended cleanups. A rules file lists
+//! one or more rules; an entry is selected when it satisfies every condition of at
+//! least one rule. Once a directory is selected nothing inside it is examined, since
+//! deleting the directory already covers it.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Format
+//!
+//! ```toml
+//! [[rule]]
+//! name = "stale build output"
+//! paths = ["**/target", "node_modules"]  # globs, relative to the scanned directory
+//! kind = "directory"                     # "file" or "directory"; any if omitted
+//! min_size = 104857600                   # bytes
+//! older_than_days = 7                    # nothing inside modified more recently
+//! ```
+//!
+//! A glob without a `/` matches a name at any depth; `*` never crosses a `/`,
+//! `**` does. Every rule needs at least one condition.
+//!
+//! ## Plans
+//!
+//! [`plan_toml`] turns a hand-made selection into a rules file that matches exactly
+//! those paths, so a cleanup planned on one machine (for example from an imported
+//! scan) can be previewed and run on the machine that holds the files.
+
+use crate::analyzer::DirectoryEntry;
+use crate::report::EntryKind;
+use crate::tree::{DirTree, NodeId};
+use anyhow::{bail, Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A rules file as written by the user.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleSpec>,
+}
+
+/// One rule as written by the user; all conditions are optional.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<EntryKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    older_than_days: Option<u64>,
+}
+
+/// A validated rule, ready to be matched.
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    paths: Option<GlobSet>,
+    kind: Option<EntryKind>,
+    min_size: Option<u64>,
+    older_than: Option<Duration>,
+}
+
+impl Rule {
+    fn compile(index: usize, spec: RuleSpec) -> Result<Self> {
+        let name = spec.name.unwrap_or_else(|| format!("rule {}", index + 1));
+        if spec.paths.is_empty()
+            && spec.kind.is_none()
+            && spec.min_size.is_none()
+            && spec.older_than_days.is_none()
+        {
+            bail!("'{}' has no conditions and would select everything", name);
+        }
+
+        let paths = if spec.paths.is_empty() {
+            None
+        } else {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &spec.paths {
+                let pattern = if pattern.contains('/') {
+                    pattern.trim_start_matches('/').to_string()
+                } else {
+                    format!("**/{}", pattern)
+                };
+                let glob = GlobBuilder::new(&pattern)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("Invalid glob '{}' in '{}'", pattern, name))?;
+                builder.add(glob);
+            }
+            Some(builder.build()?)
+        };
+
+        Ok(Self {
+            name,
+            paths,
+            kind: spec.kind,
+            min_size: spec.min_size,
+            older_than: spec
+                .older_than_days
+                .map(|days| Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY))),
+        })
+    }
+
+    /// Whether `entry`, found at `relative` below the scan root, satisfies every condition
+    fn matches(&self, entry: &DirectoryEntry, relative: &Path, now: SystemTime) -> bool {
+        if let Some(paths) = &self.paths {
+            if !paths.is_match(relative) {
+                return false;
+            }
+        }
+        if let Some(kind) = self.kind {
+            if kind != EntryKind::from_is_directory(entry.is_directory) {
+                return false;
+            }
+        }
+        if let Some(min_size) = self.min_size {
+            if entry.size_bytes < min_size {
+                return false;
+            }
+        }
+        if let Some(older_than) = self.older_than {
+            // Entries of unknown age are never old enough
+            let old_enough = entry
+                .modified
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= older_than);
+            if !old_enough {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Every rule from a rules file.
+#[derive(Debug)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+/// An entry chosen by a rule.
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    pub entry: DirectoryEntry,
+    /// Name of the first rule the entry satisfied
+    pub rule: String,
+}
+
+impl RuleSet {
+    /// Parse rules from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(text)?;
+        if file.rules.is_empty() {
+            bail!("No [[rule]] entries found");
+        }
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, spec)| Rule::compile(index, spec))
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Read and parse a rules file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read rules file '{}'", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("Invalid rules file '{}'", path.display()))
+    }
+
+    /// Entries of `tree` selected by the rules and accepted by `keep`, as of `now`.
+    ///
+    /// The scan root itself is never selected, and nothing below a selected
+    /// directory is returned. A directory that matches a rule but is not kept is
+    /// searched like one that matches none.
+    pub fn select(
+        &self,
+        tree: &DirTree,
+        now: SystemTime,
+        keep: impl Fn(&DirectoryEntry) -> bool,
+    ) -> Vec<RuleMatch> {
+        let root_path = tree.path(tree.root());
+        let mut matches = Vec::new();
+        let mut stack: Vec<NodeId> = tree.children(tree.root()).iter().rev().copied().collect();
+
+        while let Some(id) = stack.pop() {
+            let entry = tree.entry(id);
+            let relative = entry.path.strip_prefix(&root_path).unwrap_or(&entry.path);
+            match self.rules.iter().find(|r| r.matches(&entry, relative, now)) {
+                Some(rule) if keep(&entry) => matches.push(RuleMatch {
+                    rule: rule.name.clone(),
+                    entry,
+                }),
+                _ => stack.extend(tree.children(id).iter().rev()),
+            }
+        }
+
+        matches
+    }
+}
+
+/// Rules file text selecting exactly `entries`, which must all lie below `root`.
+///
+/// Each path becomes a glob anchored at the scan root with every glob character
+/// escaped, and files and directories get separate rules so an entry that changed
+/// kind since the selection is left alone.
+pub fn plan_toml(root: &Path, entries: &[DirectoryEntry]) -> Result<String> {
+    let mut files = Vec::new();
+    let mut directories = Vec::new();
+    for entry in entries {
+        let relative = entry.path.strip_prefix(root).with_context(|| {
+            format!(
+                "'{}' is not inside '{}'",
+                entry.path.display(),
+                root.display()
+            )
+        })?;
+        let Some(relative) = relative.to_str().filter(|r| !r.is_empty()) else {
+            bail!(
+                "'{}' cannot be written to a rules file",
+                entry.path.display()
+            );
+        };
+        let glob = format!("/{}", escape_glob(relative));
+        if entry.is_directory {
+            directories.push(glob);
+        } else {
+            files.push(glob);
+        }
+    }
+
+    let mut rules = Vec::new();
+    for (name, kind, paths) in [
+        ("planned files", EntryKind::File, files),
+        ("planned directories", EntryKind::Directory, directories),
+    ] {
+        if !paths.is_empty() {
+            rules.push(RuleSpec {
+                name: Some(name.to_string()),
+                paths,
+                kind: Some(kind),
+                ..RuleSpec::default()
+            });
+        }
+    }
+    Ok(toml::to_string(&RulesFile { rules })?)
+}
+
+/// `text` as a glob that matches only itself
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '{' | '}' | '\\' => {
+                escaped.push('[');
+                escaped.push(c);
+                escaped.push(']');
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    /// `project/target/` (old, 3000 bytes), `project/src/main.rs`, `notes.txt`
+    fn sample_tree(now: SystemTime) -> DirTree {
+        let mut tree = DirTree::new(PathBuf::from("/work"));
+        let project = tree.push_child(tree.root(), "project".into(), true, 0);
+        let target = tree.push_child(project, "target".into(), true, 0);
+        let artifact = tree.push_child(target, "app".into(), false, 3000);
+        let src = tree.push_child(project, "src".into(), true, 0);
+        let main = tree.push_child(src, "main.rs".into(), false, 200);
+        let notes = tree.push_child(tree.root(), "notes.txt".into(), false, 50);
+
+        for (id, age) in [(artifact, 30), (target, 30), (main, 1), (notes, 90)] {
+            tree.node_mut(id).modified = Some(now - DAY * age);
+        }
+        tree.finalize();
+        tree
+    }
+
+    fn selected(rules: &str, now: SystemTime) -> Vec<String> {
+        RuleSet::from_toml(rules)
+            .unwrap()
+            .select(&sample_tree(now), now, |_| true)
+            .into_iter()
+            .map(|m| m.entry.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_glob_selects_directory_and_skips_its_contents() {
+        let now = SystemTime::now();
+        let rules = r#"
+            [[rule]]
+            paths = ["target", "**/app"]
+        "#;
+        assert_eq!(selected(rules, now), vec!["/work/project/target"]);
+    }
+
+    #[test]
+    fn test_entries_not_kept_are_searched_instead() {
+        let now = SystemTime::now();
+        let rules = RuleSet::from_toml("[[rule]]\nolder_than_days = 7").unwrap();
+        // `target` matches, but only files are kept, so what it holds is considered
+        let found: Vec<_> = rules
+            .select(&sample_tree(now), now, |entry| !entry.is_directory)
+            .into_iter()
+            .map(|m| m.entry.path.display().to_string())
+            .collect();
+        assert_eq!(found, vec!["/work/project/target/app", "/work/notes.txt"]);
+    }
+
+    #[test]
+    fn test_globs_are_anchored_at_the_scan_root() {
+        let now = SystemTime::now();
+        assert_eq!(
+            selected(
+                "[[rule]]\npaths = [\"project/*\"]\nkind = \"directory\"",
+                now
+            ),
+            vec!["/work/project/target", "/work/project/src"]
+        );
+        assert!(selected("[[rule]]\npaths = [\"*/main.rs\"]", now).is_empty());
+    }
+
+    #[test]
+    fn test_conditions_are_combined() {
+        let now = SystemTime::now();
+        let rules = r#"
+            [[rule]]
+            name = "big and old"
+            kind = "file"
+            min_size = 100
+            older_than_days = 7
+
+            [[rule]]
+            name = "old notes"
+            paths = ["*.txt"]
+            older_than_days = 60
+        "#;
+        let matches = RuleSet::from_toml(rules)
+            .unwrap()
+            .select(&sample_tree(now), now, |_| true);
+        let found: Vec<_> = matches
+            .iter()
+            .map(|m| (m.entry.path.display().to_string(), m.rule.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                ("/work/project/target/app".to_string(), "big and old"),
+                ("/work/notes.txt".to_string(), "old notes"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_age_uses_newest_modification_inside_directories() {
+        let now = SystemTime::now();
+        let rules = "[[rule]]\nkind = \"directory\"\nolder_than_days = 7";
+        // `project` holds a recently edited file, so only `target` is old enough
+        assert_eq!(selected(rules, now), vec!["/work/project/target"]);
+    }
+
+    #[test]
+    fn test_plan_selects_exactly_the_planned_entries() {
+        let now = SystemTime::now();
+        let mut tree = DirTree::new(PathBuf::from("/work"));
+        let odd = tree.push_child(tree.root(), "odd [*?] {a,b} \\".into(), true, 0);
+        tree.push_child(odd, "x".into(), false, 10);
+        tree.push_child(tree.root(), "odd a".into(), true, 0);
+        let notes = tree.push_child(tree.root(), "notes.txt".into(), false, 5);
+        let nested = tree.push_child(tree.root(), "sub".into(), true, 0);
+        tree.push_child(nested, "notes.txt".into(), false, 5);
+        tree.finalize();
+
+        let planned = vec![tree.entry(odd), tree.entry(notes)];
+        let plan = plan_toml(Path::new("/work"), &planned).unwrap();
+        let found: Vec<_> = RuleSet::from_toml(&plan)
+            .unwrap()
+            .select(&tree, now, |_| true)
+            .into_iter()
+            .map(|m| m.entry.path)
+            .collect();
+
+        let mut expected: Vec<_> = planned.into_iter().map(|e| e.path).collect();
+        expected.sort();
+        let mut found = found;
+        found.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_plan_rejects_entries_outside_the_root() {
+        let entry = DirectoryEntry::new(PathBuf::from("/elsewhere/file"), 1, false);
+        assert!(plan_toml(Path::new("/work"), &[entry]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!(RuleSet::from_toml("").is_err());
+        assert!(RuleSet::from_toml("[[rule]]\nname = \"everything\"").is_err());
+        assert!(RuleSet::from_toml("[[rule]]\npaths = [\"[\"]").is_err());
+        assert!(RuleSet::from_toml("[[rule]]\nmin_sise = 10").is_err());
+        assert!(RuleSet::from_toml("[[rule]]\nkind = \"socket\"").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! # Serialization Helpers
+//!
+//! `serialize_with` functions for standard types that serde cannot write on its own
+//! or that need a friendlier representation in reports and exports, plus lossless
+//! path (de)serialization for files the tool reads back.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Paths are written as strings; bytes that are not valid UTF-8 are replaced
+pub fn path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+pub fn optional_path<S: Serializer>(
+    path: &Option<PathBuf>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match path {
+        Some(path) => serializer.serialize_some(&path.to_string_lossy()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// `io::ErrorKind` has no serde support, so it is written by name
+pub fn error_kind<S: Serializer>(
+    kind: &Option<io::ErrorKind>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match kind {
+        Some(kind) => serializer.serialize_some(&format!("{:?}", kind)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Durations are written as fractional milliseconds
+pub fn millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// Timestamps are written as whole seconds since the Unix epoch
+pub fn optional_unix_secs<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time.and_then(|t| t.duration_since(UNIX_EPOCH).ok()) {
+        Some(since_epoch) => serializer.serialize_some(&since_epoch.as_secs()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Paths that must round-trip exactly: those that are not valid UTF-8 are stored
+/// as raw bytes instead of a string
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PathRepr {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl PathRepr {
+    fn from_path(path: &Path) -> Self {
+        if let Some(text) = path.to_str() {
+            return PathRepr::Text(text.to_string());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            PathRepr::Bytes(path.as_os_str().as_bytes().to_vec())
+        }
+        #[cfg(not(unix))]
+        {
+            PathRepr::Text(path.to_string_lossy().into_owned())
+        }
+    }
+
+    fn into_path(self) -> PathBuf {
+        match self {
+            PathRepr::Text(text) => PathBuf::from(text),
+            #[cfg(unix)]
+            PathRepr::Bytes(bytes) => {
+                use std::os::unix::ffi::OsStringExt;
+                PathBuf::from(std::ffi::OsString::from_vec(bytes))
+            }
+            #[cfg(not(unix))]
+            PathRepr::Bytes(bytes) => PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+}
+
+pub mod path_repr {
+    use super::PathRepr;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::{Path, PathBuf};
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        PathRepr::from_path(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        PathRepr::deserialize(deserializer).map(PathRepr::into_path)
+    }
+}
+
+pub mod optional_path_repr {
+    use super::PathRepr;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::PathBuf;
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_deref()
+            .map(PathRepr::from_path)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PathBuf>, D::Error> {
+        Option::<PathRepr>::deserialize(deserializer).map(|repr| repr.map(PathRepr::into_path))
+    }
+}
@@ -0,0 +1,352 @@
+//! # Scan Snapshots
+//!
+//! Saves the entries of an analysis to a file so a later scan can be compared
+//! against it, answering "what grew since last week?".
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Format
+//!
+//! A snapshot is one JSON document holding the scan root, when it was taken, the
+//! depth it covers, whole-scan totals, and every entry down to that depth with its
+//! sizes, file count and newest modification time. Entry paths are relative to the
+//! root, so snapshots of a filesystem mounted in different places still line up.
+//!
+//! ## Diffing
+//!
+//! Entries are matched by relative path, down to the shallower of the two depths.
+//! An entry only on the new side is added, only on the old side removed; entries
+//! whose size changed grew or shrank. An entry that turned from a file into a
+//! directory, or back, counts as removed and added. Unchanged entries are omitted.
+
+use crate::analyzer::SizeMode;
+use crate::report::EntryKind;
+use crate::serialize;
+use crate::tree::DirTree;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version written into new snapshots; others are rejected when loading
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// One entry of a snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path relative to the snapshot root
+    #[serde(with = "serialize::path_repr")]
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub size_bytes: u64,
+    pub allocated_bytes: u64,
+    pub file_count: u64,
+    /// Newest modification inside the entry, in seconds since the Unix epoch
+    pub modified: Option<u64>,
+}
+
+impl SnapshotEntry {
+    /// Size of this entry as selected by `mode`
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    /// Levels below the snapshot root, 1 for its immediate children
+    fn depth(&self) -> usize {
+        self.path.components().count()
+    }
+}
+
+/// A saved analysis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    #[serde(with = "serialize::path_repr")]
+    pub root: PathBuf,
+    /// When the scan was taken, in seconds since the Unix epoch
+    pub taken_at: u64,
+    /// Levels below the root that the entries cover
+    pub depth: usize,
+    pub size_bytes: u64,
+    pub allocated_bytes: u64,
+    pub file_count: u64,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// Snapshot of `tree` down to `depth` levels, taken now
+    pub fn from_tree(tree: &DirTree, depth: usize) -> Self {
+        let root = tree.path(tree.root());
+        let node = tree.node(tree.root());
+        let entries = tree
+            .entries_to_depth(tree.root(), depth.max(1))
+            .into_iter()
+            .map(|entry| SnapshotEntry {
+                path: entry
+                    .path
+                    .strip_prefix(&root)
+                    .unwrap_or(&entry.path)
+                    .to_path_buf(),
+                kind: EntryKind::from_is_directory(entry.is_directory),
+                size_bytes: entry.size_bytes,
+                allocated_bytes: entry.allocated_bytes,
+                file_count: entry.file_count,
+                modified: entry
+                    .modified
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|since_epoch| since_epoch.as_secs()),
+            })
+            .collect();
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            root,
+            taken_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+            depth: depth.max(1),
+            size_bytes: node.size_bytes,
+            allocated_bytes: node.allocated_bytes,
+            file_count: node.file_count,
+            entries,
+        }
+    }
+
+    /// Total size of the scan as selected by `mode`
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    /// Write the snapshot to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec(self)?;
+        fs::write(path, json).with_context(|| format!("Cannot write snapshot '{}'", path.display()))
+    }
+
+    /// Read a snapshot written by [`Snapshot::save`]
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Cannot read snapshot '{}'", path.display()))?;
+        let snapshot: Self = serde_json::from_slice(&bytes)
+            .with_context(|| format!("'{}' is not a snapshot", path.display()))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!(
+                "Snapshot '{}' has unsupported version {}",
+                path.display(),
+                snapshot.version
+            );
+        }
+        Ok(snapshot)
+    }
+}
+
+/// How an entry changed between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Added,
+    Removed,
+    Grew,
+    Shrank,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Grew => "grew",
+            Change::Shrank => "shrank",
+        }
+    }
+}
+
+/// One changed entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntryChange {
+    /// Path relative to the snapshot roots
+    #[serde(serialize_with = "serialize::path")]
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub change: Change,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    /// New size minus old size, in bytes
+    pub delta: i64,
+}
+
+/// Every change between two snapshots, largest absolute delta first.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    #[serde(serialize_with = "serialize::path")]
+    pub root: PathBuf,
+    pub old_taken_at: u64,
+    pub new_taken_at: u64,
+    /// Levels below the root that were compared
+    pub depth: usize,
+    pub size_mode: SizeMode,
+    /// Change of the whole scan, in bytes
+    pub total_delta: i64,
+    pub changes: Vec<EntryChange>,
+}
+
+/// Compares `old` with `new`, sizing entries by `size_mode`.
+pub fn diff(old: &Snapshot, new: &Snapshot, size_mode: SizeMode) -> SnapshotDiff {
+    let depth = old.depth.min(new.depth);
+    let index = |snapshot: &Snapshot| -> HashMap<(PathBuf, EntryKind), u64> {
+        snapshot
+            .entries
+            .iter()
+            .filter(|entry| entry.depth() <= depth)
+            .map(|entry| ((entry.path.clone(), entry.kind), entry.size(size_mode)))
+            .collect()
+    };
+    let (before, after) = (index(old), index(new));
+
+    let mut changes = Vec::new();
+    for ((path, kind), &new_size) in &after {
+        let old_size = before.get(&(path.clone(), *kind)).copied();
+        let change = match old_size {
+            None => Change::Added,
+            Some(old_size) if new_size > old_size => Change::Grew,
+            Some(old_size) if new_size < old_size => Change::Shrank,
+            Some(_) => continue,
+        };
+        changes.push(EntryChange {
+            path: path.clone(),
+            kind: *kind,
+            change,
+            old_size,
+            new_size: Some(new_size),
+            delta: delta(old_size.unwrap_or(0), new_size),
+        });
+    }
+    for ((path, kind), &old_size) in &before {
+        if !after.contains_key(&(path.clone(), *kind)) {
+            changes.push(EntryChange {
+                path: path.clone(),
+                kind: *kind,
+                change: Change::Removed,
+                old_size: Some(old_size),
+                new_size: None,
+                delta: delta(old_size, 0),
+            });
+        }
+    }
+    changes.sort_by(|a, b| {
+        b.delta
+            .unsigned_abs()
+            .cmp(&a.delta.unsigned_abs())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    SnapshotDiff {
+        root: new.root.clone(),
+        old_taken_at: old.taken_at,
+        new_taken_at: new.taken_at,
+        depth,
+        size_mode,
+        total_delta: delta(old.size(size_mode), new.size(size_mode)),
+        changes,
+    }
+}
+
+/// `new - old` as a signed byte count
+fn delta(old: u64, new: u64) -> i64 {
+    if new >= old {
+        i64::try_from(new - old).unwrap_or(i64::MAX)
+    } else {
+        i64::try_from(old - new).map_or(i64::MIN, |shrink| -shrink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// `/data` with `logs/` (app.log) and `cache.bin`, sized as given
+    fn snapshot(log: u64, cache: Option<u64>, extra: Option<u64>) -> Snapshot {
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let logs = tree.push_child(tree.root(), "logs".into(), true, 0);
+        tree.push_child(logs, "app.log".into(), false, log);
+        if let Some(cache) = cache {
+            tree.push_child(tree.root(), "cache.bin".into(), false, cache);
+        }
+        if let Some(extra) = extra {
+            tree.push_child(logs, "new.log".into(), false, extra);
+        }
+        tree.finalize();
+        Snapshot::from_tree(&tree, 2)
+    }
+
+    #[test]
+    fn test_diff_lists_changes_by_absolute_delta() {
+        let old = snapshot(1000, Some(300), None);
+        let new = snapshot(900, None, Some(5000));
+        let diff = diff(&old, &new, SizeMode::Apparent);
+
+        let found: Vec<(&str, Change, i64)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.path.to_str().unwrap(), c.change, c.delta))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("logs/new.log", Change::Added, 5000),
+                ("logs", Change::Grew, 4900),
+                ("cache.bin", Change::Removed, -300),
+                ("logs/app.log", Change::Shrank, -100),
+            ]
+        );
+        assert_eq!(diff.total_delta, 4600);
+        assert_eq!(diff.changes[2].new_size, None);
+    }
+
+    #[test]
+    fn test_unchanged_entries_and_deeper_levels_are_ignored() {
+        let old = snapshot(1000, Some(300), None);
+        let mut new = snapshot(1000, Some(300), None);
+        assert!(diff(&old, &new, SizeMode::Apparent).changes.is_empty());
+
+        // Only the first level is compared when one side is shallower
+        new.depth = 1;
+        new.entries.retain(|entry| entry.depth() == 1);
+        assert!(
+            diff(&snapshot(9000, Some(300), None), &new, SizeMode::Apparent)
+                .changes
+                .iter()
+                .all(|change| change.path == Path::new("logs"))
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("week1.json");
+        let saved = snapshot(1000, Some(300), None);
+
+        saved.save(&file).unwrap();
+        let loaded = Snapshot::load(&file).unwrap();
+        assert_eq!(loaded, saved);
+        assert_eq!(loaded.entries[0].path, PathBuf::from("logs"));
+
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        json["version"] = 99.into();
+        fs::write(&file, json.to_string()).unwrap();
+        let error = Snapshot::load(&file).unwrap_err();
+        assert!(error.to_string().contains("unsupported version 99"));
+    }
+}
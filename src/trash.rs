@@ -0,0 +1,429 @@
+//! # Trash Support
+//!
+//! Moves entries into the trash following the freedesktop.org Trash specification
+//! instead of removing them, so a mistaken cleanup can be undone from any desktop
+//! file manager.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Layout
+//!
+//! - Entries on the same filesystem as the home trash go to
+//!   `$XDG_DATA_HOME/Trash` (`~/.local/share/Trash` by default).
+//! - Entries on other filesystems go to the top directory of their mount:
+//!   `$topdir/.Trash/$uid` when an administrator prepared a sticky `.Trash`,
+//!   otherwise `$topdir/.Trash-$uid`.
+//!
+//! Every trashed entry is renamed into `files/` and described by a matching
+//! `info/<name>.trashinfo` holding its original path and deletion date. Entries are
+//! never copied across filesystems, so trashing is a rename and cannot half-fail.
+//!
+//! ## Platforms
+//!
+//! On macOS entries go where Finder shows them: straight into `~/.Trash`, or into
+//! `$topdir/.Trashes/$uid` on other volumes, without `.trashinfo` files. Elsewhere
+//! outside Unix there is no trash; [`Trash::for_current_user`] returns `None` and
+//! entries can only be deleted permanently.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::platform::PlatformUtils;
+
+/// Where a trashed entry ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedEntry {
+    /// The entry itself, inside the trash's `files/` directory
+    pub files_path: PathBuf,
+    /// Its `.trashinfo` metadata, inside the trash's `info/` directory, where the
+    /// trash keeps any
+    pub info_path: Option<PathBuf>,
+}
+
+/// How the directories of a trash are organized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// freedesktop.org: entries in `files/`, described in `info/`
+    Freedesktop,
+    /// Finder: entries directly in the trash directory, without metadata
+    #[cfg(target_os = "macos")]
+    Finder,
+}
+
+/// A user's trash: the home trash plus per-mount trash directories found on demand.
+#[derive(Debug, Clone)]
+pub struct Trash {
+    home: PathBuf,
+    layout: Layout,
+}
+
+impl Trash {
+    /// Whether this platform has a trash at all
+    pub const SUPPORTED: bool = cfg!(unix);
+
+    /// Trash whose home trash directory is `home` (the directory holding `files/`
+    /// and `info/`)
+    pub fn new<P: Into<PathBuf>>(home: P) -> Self {
+        Self {
+            home: home.into(),
+            layout: Layout::Freedesktop,
+        }
+    }
+
+    /// The current user's trash, located through `$XDG_DATA_HOME` or `$HOME`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn for_current_user() -> Option<Self> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })?;
+        Some(Self::new(data_home.join("Trash")))
+    }
+
+    /// The current user's trash, the `~/.Trash` shown by Finder
+    #[cfg(target_os = "macos")]
+    pub fn for_current_user() -> Option<Self> {
+        let home = std::env::var_os("HOME")?;
+        Some(Self {
+            home: PathBuf::from(home).join(".Trash"),
+            layout: Layout::Finder,
+        })
+    }
+
+    /// There is no trash on this platform
+    #[cfg(not(unix))]
+    pub fn for_current_user() -> Option<Self> {
+        None
+    }
+
+    /// Move `path` into the trash on its own filesystem and record where it came from
+    pub fn move_to_trash(&self, path: &Path) -> io::Result<TrashedEntry> {
+        let path = absolute_path(path)?;
+        let name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+        let (trash_dir, topdir) = self.trash_dir_for(&path)?;
+
+        // Relative paths are allowed, and preferred, in per-mount trash directories
+        // so that they stay valid if the filesystem is mounted elsewhere
+        let recorded = match &topdir {
+            Some(topdir) => path.strip_prefix(topdir).unwrap_or(&path),
+            None => &path,
+        };
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(recorded),
+            deletion_date()
+        );
+
+        // Reserve a unique name by creating the info file exclusively, as the spec
+        // requires, then move the entry next to it
+        let (files_dir, info_dir) = self.layout.directories(&trash_dir);
+        for attempt in 1.. {
+            let mut candidate = name.to_os_string();
+            if attempt > 1 {
+                candidate.push(format!(".{}", attempt));
+            }
+            let files_path = files_dir.join(&candidate);
+            if files_path.symlink_metadata().is_ok() {
+                continue;
+            }
+            let Some(info_dir) = &info_dir else {
+                fs::rename(&path, &files_path)?;
+                return Ok(TrashedEntry {
+                    files_path,
+                    info_path: None,
+                });
+            };
+            let mut info_name = candidate;
+            info_name.push(".trashinfo");
+            let info_path = info_dir.join(info_name);
+
+            let mut file = match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            let moved = file
+                .write_all(info.as_bytes())
+                .and_then(|()| fs::rename(&path, &files_path));
+            if let Err(e) = moved {
+                let _ = fs::remove_file(&info_path);
+                return Err(e);
+            }
+            return Ok(TrashedEntry {
+                files_path,
+                info_path: Some(info_path),
+            });
+        }
+        unreachable!("candidate names are unbounded")
+    }
+
+    /// Trash directory on the same filesystem as `path`, and the mount's top
+    /// directory when it is not the home trash
+    fn trash_dir_for(&self, path: &Path) -> io::Result<(PathBuf, Option<PathBuf>)> {
+        let parent = path.parent().unwrap_or(path);
+        let device = device_of(parent)?;
+
+        if let Ok(()) = self.layout.create(&self.home) {
+            if device_of(&self.home)? == device {
+                return Ok((self.home.clone(), None));
+            }
+        }
+
+        let topdir = mount_root(parent, device)?;
+        let uid = current_uid();
+
+        // Finder looks in `.Trashes`, with a directory per user
+        #[cfg(target_os = "macos")]
+        if self.layout == Layout::Finder {
+            let dir = topdir.join(".Trashes").join(uid.to_string());
+            return match self.layout.create(&dir) {
+                Ok(()) => Ok((dir, Some(topdir))),
+                Err(e) => Err(no_trash_on(&topdir, e)),
+            };
+        }
+
+        // Method 1: a shared, sticky, non-symlink `.Trash` with a directory per user
+        let shared = topdir.join(".Trash");
+        if is_sticky_dir(&shared) {
+            let dir = shared.join(uid.to_string());
+            if self.layout.create(&dir).is_ok() {
+                return Ok((dir, Some(topdir)));
+            }
+        }
+
+        // Method 2: a private `.Trash-$uid` at the top of the mount
+        let dir = topdir.join(format!(".Trash-{}", uid));
+        match self.layout.create(&dir) {
+            Ok(()) => Ok((dir, Some(topdir))),
+            Err(e) => Err(no_trash_on(&topdir, e)),
+        }
+    }
+}
+
+impl Layout {
+    /// Where entries and, if kept, their `.trashinfo` files go in `trash_dir`
+    fn directories(self, trash_dir: &Path) -> (PathBuf, Option<PathBuf>) {
+        match self {
+            Layout::Freedesktop => (trash_dir.join("files"), Some(trash_dir.join("info"))),
+            #[cfg(target_os = "macos")]
+            Layout::Finder => (trash_dir.to_path_buf(), None),
+        }
+    }
+
+    /// Create the directories of a trash at `dir`, private to the user
+    fn create(self, dir: &Path) -> io::Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        let (files_dir, info_dir) = self.directories(dir);
+        builder.create(files_dir)?;
+        info_dir.map_or(Ok(()), |info_dir| builder.create(info_dir))
+    }
+}
+
+/// Error for a mount at `topdir` where no trash directory could be set up
+fn no_trash_on(topdir: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!(
+            "no usable trash directory on the filesystem mounted at '{}': {}",
+            topdir.display(),
+            e
+        ),
+    )
+}
+
+/// `path` made absolute without resolving the entry itself, which may be a symlink
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+/// Highest ancestor of `dir` that is still on `device`
+fn mount_root(dir: &Path, device: u64) -> io::Result<PathBuf> {
+    let mut root = dir.to_path_buf();
+    for ancestor in dir.ancestors().skip(1) {
+        if device_of(ancestor)? != device {
+            break;
+        }
+        root = ancestor.to_path_buf();
+    }
+    Ok(root)
+}
+
+fn device_of(path: &Path) -> io::Result<u64> {
+    let metadata = fs::metadata(path)?;
+    PlatformUtils::file_id(&metadata)
+        .map(|id| id.device)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the trash is not supported on this platform",
+            )
+        })
+}
+
+fn is_sticky_dir(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.symlink_metadata()
+            .map(|meta| meta.is_dir() && meta.permissions().mode() & 0o1000 != 0)
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+fn current_uid() -> u32 {
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid has no preconditions and cannot fail
+        unsafe { libc::getuid() }
+    }
+
+    #[cfg(not(unix))]
+    {
+        0
+    }
+}
+
+/// Percent-encode a path the way the spec expects in `Path=` lines
+fn encode_path(path: &Path) -> String {
+    let bytes = PlatformUtils::path_bytes(path);
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes.iter() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Current local time as `YYYY-MM-DDThh:mm:ss`
+fn deletion_date() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    PlatformUtils::local_time(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn read_info(entry: &TrashedEntry) -> String {
+        fs::read_to_string(entry.info_path.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_move_file_to_home_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::new(temp_dir.path().join("Trash"));
+        let file_path = temp_dir.path().join("build cache.bin");
+        fs::write(&file_path, b"data").unwrap();
+
+        let entry = trash.move_to_trash(&file_path).unwrap();
+
+        assert!(!file_path.exists());
+        assert_eq!(fs::read(&entry.files_path).unwrap(), b"data");
+        assert_eq!(
+            entry.files_path,
+            temp_dir.path().join("Trash/files/build cache.bin")
+        );
+        let info = read_info(&entry);
+        assert!(info.starts_with("[Trash Info]\n"));
+        let original = fs::canonicalize(temp_dir.path())
+            .unwrap()
+            .join("build cache.bin");
+        assert!(info.contains(&format!("Path={}\n", encode_path(&original))));
+        assert!(info.contains("/build%20cache.bin\n"));
+        assert!(info.contains("DeletionDate="));
+    }
+
+    #[test]
+    fn test_move_directory_to_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::new(temp_dir.path().join("Trash"));
+        let dir_path = temp_dir.path().join("target");
+        fs::create_dir(&dir_path).unwrap();
+        File::create(dir_path.join("artifact.o")).unwrap();
+
+        let entry = trash.move_to_trash(&dir_path).unwrap();
+
+        assert!(!dir_path.exists());
+        assert!(entry.files_path.join("artifact.o").exists());
+    }
+
+    #[test]
+    fn test_name_collisions_get_unique_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::new(temp_dir.path().join("Trash"));
+        let file_path = temp_dir.path().join("notes.txt");
+
+        File::create(&file_path).unwrap();
+        let first = trash.move_to_trash(&file_path).unwrap();
+        File::create(&file_path).unwrap();
+        let second = trash.move_to_trash(&file_path).unwrap();
+
+        assert_ne!(first.files_path, second.files_path);
+        assert!(second.files_path.ends_with("notes.txt.2"));
+        assert!(second.info_path.unwrap().ends_with("notes.txt.2.trashinfo"));
+        assert!(first.files_path.exists() && second.files_path.exists());
+    }
+
+    #[test]
+    fn test_missing_entry_leaves_no_info_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::new(temp_dir.path().join("Trash"));
+
+        assert!(trash
+            .move_to_trash(&temp_dir.path().join("missing.txt"))
+            .is_err());
+        assert_eq!(
+            fs::read_dir(temp_dir.path().join("Trash/info"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(
+            encode_path(Path::new("/home/me/My Files/50%.txt")),
+            "/home/me/My%20Files/50%25.txt"
+        );
+    }
+}
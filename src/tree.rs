@@ -0,0 +1,950 @@
+//! # Directory Tree Module
+//!
+//! Persistent in-memory model of a scanned directory hierarchy.
+//! The tree is built in a single filesystem traversal and keeps every walked
+//! node, so drilling down, re-sorting and filtering never touch the disk again.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Key Features
+//!
+//! - **Single Traversal**: Sizes, file counts and errors are collected in one walk
+//! - **Parent/Child Links**: Navigate up and down without path lookups
+//! - **Arena Storage**: Nodes live in one `Vec` and are addressed by [`NodeId`]
+//! - **Cheap Views**: Any node can be turned into a [`DirectoryEntry`] for display
+//! - **Error Tracking**: Unreadable entries are recorded on the node where they occurred
+//! - **Partial Sizes**: Directories with unreadable contents are flagged as knowingly partial
+//! - **Hard-Link Aware**: Each inode is counted once per directory, shared bytes are reported
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! let tree = DirTree::scan(Path::new("/home/user/projects"));
+//! for entry in tree.child_entries(tree.root()) {
+//!     println!("{} {}", entry.size_human, entry.path.display());
+//! }
+//! ```
+
+use crate::analyzer::{DirectoryEntry, SizeMode};
+use crate::cache::{CachedDirectory, CachedEntry};
+use crate::filter::ExcludedTotals;
+use crate::git::GitInfo;
+use crate::platform::{FileId, PlatformUtils};
+use crate::walker::{self, WalkOptions};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Index of a node inside a [`DirTree`].
+pub type NodeId = usize;
+
+/// A single file or directory in the tree.
+///
+/// Directory sizes and file counts are recursive totals once the tree has been
+/// finalized; before that they only hold the node's own contribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    /// File name of this node (the full scan root path for the root node)
+    pub name: OsString,
+    /// Parent directory, `None` for the root
+    pub parent: Option<NodeId>,
+    /// Child nodes, sorted largest first after finalization
+    pub children: Vec<NodeId>,
+    /// True if this node is a directory
+    pub is_directory: bool,
+    /// Apparent size in bytes (recursive for directories)
+    pub size_bytes: u64,
+    /// Bytes allocated on disk (recursive for directories, including their own blocks)
+    pub allocated_bytes: u64,
+    /// Apparent bytes of hard-linked files that are also linked from outside this node,
+    /// so deleting this node alone does not free them
+    pub shared_bytes: u64,
+    /// Allocated bytes of the same hard-linked files as `shared_bytes`
+    pub shared_allocated_bytes: u64,
+    /// Number of non-directory entries at or below this node
+    pub file_count: u64,
+    /// Errors encountered while reading this node or its direct children
+    pub errors: Vec<String>,
+    /// True if this node or anything below it could not be fully read,
+    /// meaning `size_bytes` is a lower bound
+    pub partial: bool,
+    /// Most recent modification time of this node or anything below it
+    pub modified: Option<SystemTime>,
+    /// Status in the enclosing git work tree, once tagged with
+    /// [`git::tag_tree`](crate::git::tag_tree)
+    pub git: Option<GitInfo>,
+    /// Type of the filesystem mounted on this directory, if it is a mount point
+    pub mount: Option<String>,
+}
+
+impl TreeNode {
+    /// Size of this node as selected by `mode`.
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Arena-backed directory tree produced by a single scan.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut tree = DirTree::new(PathBuf::from("/data"));
+/// let logs = tree.push_child(tree.root(), "logs".into(), true, 0);
+/// tree.push_child(logs, "app.log".into(), false, 4096);
+/// tree.finalize();
+/// assert_eq!(tree.node(tree.root()).size_bytes, 4096);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirTree {
+    root_path: PathBuf,
+    nodes: Vec<TreeNode>,
+    hard_links: Vec<HardLink>,
+    /// What the walk left out; not part of any node's totals
+    excluded: ExcludedTotals,
+    /// Directories whose listing was reused from the scan cache
+    cached_directories: u64,
+}
+
+/// A file node whose data is reachable through more than one directory entry.
+#[derive(Debug, Clone, Copy)]
+struct HardLink {
+    node: NodeId,
+    file_id: FileId,
+    link_count: u64,
+    /// Root of the subtree in which this link has already been resolved
+    resolved_below: Option<NodeId>,
+}
+
+impl DirTree {
+    /// Creates a tree containing only the root directory.
+    pub fn new(root_path: PathBuf) -> Self {
+        let root = TreeNode {
+            name: root_path.clone().into_os_string(),
+            parent: None,
+            children: Vec::new(),
+            is_directory: true,
+            size_bytes: 0,
+            allocated_bytes: 0,
+            shared_bytes: 0,
+            shared_allocated_bytes: 0,
+            file_count: 0,
+            errors: Vec::new(),
+            partial: false,
+            modified: None,
+            git: None,
+            mount: None,
+        };
+        Self {
+            root_path,
+            nodes: vec![root],
+            hard_links: Vec::new(),
+            excluded: ExcludedTotals::default(),
+            cached_directories: 0,
+        }
+    }
+
+    /// Walks `path` once and returns the finalized tree of everything below it.
+    ///
+    /// The walk is always fully recursive so directory totals are complete; how
+    /// much of the tree is shown is decided by the caller. Symbolic links are
+    /// never followed; they are recorded as leaf entries with the size of the link
+    /// itself. Entries that cannot be read are recorded as errors on the closest
+    /// readable ancestor and the walk continues.
+    ///
+    /// Hard links are recorded but not yet deduplicated; call
+    /// [`DirTree::resolve_hard_links`] on the result, and again once it has been
+    /// grafted into a larger tree.
+    pub fn scan(path: &Path) -> Self {
+        Self::scan_with(path, WalkOptions::default()).0
+    }
+
+    /// Like [`DirTree::scan`], with the cache, progress reporting and filter in
+    /// `options`. Directories that have not changed since they were cached are not
+    /// read again: their cached listing is used instead. Excluded entries are left
+    /// out of the tree and only counted in [`DirTree::excluded`].
+    ///
+    /// Also returns the listing of every directory walked, to cache for the next scan.
+    pub fn scan_with(path: &Path, options: WalkOptions<'_>) -> (Self, Vec<CachedDirectory>) {
+        let mut tree = Self::new(path.to_path_buf());
+        let mut listings = Vec::new();
+
+        let root_meta = match path.symlink_metadata() {
+            Ok(meta) => meta,
+            Err(e) => {
+                tree.nodes[0]
+                    .errors
+                    .push(format!("{}: {}", path.display(), e));
+                tree.nodes[0].partial = true;
+                if let Some(progress) = options.progress {
+                    progress.record(0, 0, 1);
+                }
+                return (tree, listings);
+            }
+        };
+        tree.nodes[0].allocated_bytes = PlatformUtils::allocated_size(&root_meta);
+        tree.nodes[0].modified = root_meta.modified().ok();
+        if !root_meta.is_dir() {
+            tree.nodes[0].is_directory = false;
+            tree.nodes[0].size_bytes = root_meta.len();
+            tree.nodes[0].file_count = 1;
+            tree.record_hard_link(0, &root_meta);
+            if let Some(progress) = options.progress {
+                progress.record(1, root_meta.len(), 0);
+            }
+            return (tree, listings);
+        }
+
+        if !options.stays_on_device(&root_meta) {
+            return (tree, listings);
+        }
+
+        // Directories are read in parallel, then added in one pass from the root
+        let mut directories = walker::read_tree(path, root_meta, options);
+        let mut pending = vec![(tree.root(), 0)];
+        while let Some((id, slot)) = pending.pop() {
+            let read = &mut directories[slot];
+            tree.nodes[id].errors.append(&mut read.errors);
+            tree.excluded.merge(&read.excluded);
+            tree.cached_directories += u64::from(read.from_cache);
+            listings.extend(read.listing.take());
+            for (entry, child_slot) in std::mem::take(&mut read.entries) {
+                let child = tree.push_entry(id, &entry);
+                if let Some(child_slot) = child_slot {
+                    pending.push((child, child_slot));
+                }
+            }
+        }
+
+        tree.finalize();
+        (tree, listings)
+    }
+
+    /// Appends `entry` under `parent`. Directories contribute no apparent size of
+    /// their own, only their blocks.
+    fn push_entry(&mut self, parent: NodeId, entry: &CachedEntry) -> NodeId {
+        let id = self.push_child(
+            parent,
+            entry.name.clone().into_os_string(),
+            entry.is_directory,
+            entry.size_bytes,
+        );
+        self.nodes[id].allocated_bytes = entry.allocated_bytes;
+        self.nodes[id].modified = entry.modified;
+        if let Some((file_id, link_count)) = entry.hard_link {
+            self.push_hard_link(id, file_id, link_count);
+        }
+        id
+    }
+
+    /// Remembers `id` if its data has more than one directory entry.
+    fn record_hard_link(&mut self, id: NodeId, metadata: &std::fs::Metadata) {
+        let link_count = PlatformUtils::link_count(metadata);
+        if link_count < 2 {
+            return;
+        }
+        if let Some(file_id) = PlatformUtils::file_id(metadata) {
+            self.push_hard_link(id, file_id, link_count);
+        }
+    }
+
+    /// Id of the root node.
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    /// Returns the node with the given id.
+    pub fn node(&self, id: NodeId) -> &TreeNode {
+        &self.nodes[id]
+    }
+
+    /// Mutable access to a node, for building trees from other sources than a scan.
+    pub fn node_mut(&mut self, id: NodeId) -> &mut TreeNode {
+        &mut self.nodes[id]
+    }
+
+    /// Children of `id`, largest first after finalization.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    /// Full filesystem path of `id`, rebuilt from the chain of ancestor names.
+    pub fn path(&self, id: NodeId) -> PathBuf {
+        let mut names = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(&self.nodes[current].name);
+            current = parent;
+        }
+
+        let mut path = self.root_path.clone();
+        for name in names.into_iter().rev() {
+            path.push(name);
+        }
+        path
+    }
+
+    /// Node at `relative` below the root, following child names.
+    pub fn find(&self, relative: &Path) -> Option<NodeId> {
+        relative.iter().try_fold(self.root(), |id, name| {
+            self.nodes[id]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| self.nodes[child].name == name)
+        })
+    }
+
+    /// Number of levels between `id` and the root (0 for the root itself).
+    pub fn depth(&self, id: NodeId) -> usize {
+        let mut depth = 0;
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            depth += 1;
+            current = parent;
+        }
+        depth
+    }
+
+    /// Builds a display entry for `id`.
+    pub fn entry(&self, id: NodeId) -> DirectoryEntry {
+        let node = &self.nodes[id];
+        let mut entry = DirectoryEntry::new(self.path(id), node.size_bytes, node.is_directory);
+        entry.allocated_bytes = node.allocated_bytes;
+        entry.shared_bytes = node.shared_bytes;
+        entry.shared_allocated_bytes = node.shared_allocated_bytes;
+        entry.file_count = node.file_count;
+        entry.depth = self.depth(id);
+        entry.is_partial = node.partial;
+        entry.modified = node.modified;
+        entry.git = node.git;
+        entry.mount = node.mount.clone();
+        entry
+    }
+
+    /// Display entries for everything below `id` down to `max_depth` levels.
+    ///
+    /// Entries are returned in pre-order, so each directory is immediately
+    /// followed by its own (already sorted) children.
+    pub fn entries_to_depth(&self, id: NodeId, max_depth: usize) -> Vec<DirectoryEntry> {
+        let mut entries = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> = self.nodes[id]
+            .children
+            .iter()
+            .rev()
+            .map(|&child| (child, 1))
+            .collect();
+
+        while let Some((node, depth)) = stack.pop() {
+            entries.push(self.entry(node));
+            if depth < max_depth {
+                stack.extend(
+                    self.nodes[node]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, depth + 1)),
+                );
+            }
+        }
+
+        entries
+    }
+
+    /// Display entries for the children of `id`, in the tree's current order.
+    pub fn child_entries(&self, id: NodeId) -> Vec<DirectoryEntry> {
+        self.nodes[id]
+            .children
+            .iter()
+            .map(|&child| self.entry(child))
+            .collect()
+    }
+
+    /// Entries the walk left out because of a [`PathFilter`](crate::filter::PathFilter).
+    pub fn excluded(&self) -> &ExcludedTotals {
+        &self.excluded
+    }
+
+    /// Number of directories whose listing was reused from the scan cache; the
+    /// sizes of files in them may be stale.
+    pub fn cached_directories(&self) -> u64 {
+        self.cached_directories
+    }
+
+    /// Adds `excluded` to what the walk left out.
+    pub fn record_excluded(&mut self, excluded: &ExcludedTotals) {
+        self.excluded.merge(excluded);
+    }
+
+    /// Every error recorded anywhere in the tree.
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.errors.iter().map(String::as_str))
+    }
+
+    /// Appends a child under `parent` and returns its id.
+    ///
+    /// The allocated size starts out equal to `size_bytes`. Sizes are not
+    /// propagated until [`DirTree::finalize`] is called.
+    pub fn push_child(
+        &mut self,
+        parent: NodeId,
+        name: OsString,
+        is_directory: bool,
+        size_bytes: u64,
+    ) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(TreeNode {
+            name,
+            parent: Some(parent),
+            children: Vec::new(),
+            is_directory,
+            size_bytes,
+            allocated_bytes: size_bytes,
+            shared_bytes: 0,
+            shared_allocated_bytes: 0,
+            file_count: u64::from(!is_directory),
+            errors: Vec::new(),
+            partial: false,
+            modified: None,
+            git: None,
+            mount: None,
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Marks file node `id` as one of `link_count` links to the inode `file_id`,
+    /// to be deduplicated by [`DirTree::resolve_hard_links`].
+    pub fn push_hard_link(&mut self, id: NodeId, file_id: FileId, link_count: u64) {
+        self.hard_links.push(HardLink {
+            node: id,
+            file_id,
+            link_count,
+            resolved_below: None,
+        });
+    }
+
+    /// Every hard-linked file node with its inode and total link count.
+    pub fn hard_links(&self) -> impl Iterator<Item = (NodeId, FileId, u64)> + '_ {
+        self.hard_links
+            .iter()
+            .map(|link| (link.node, link.file_id, link.link_count))
+    }
+
+    /// Moves every node of `subtree` under `parent`, keeping its totals.
+    ///
+    /// `subtree` must already be finalized; its totals are added to all of
+    /// `parent`'s ancestors so this tree stays consistent without a re-walk.
+    pub fn graft(&mut self, parent: NodeId, subtree: DirTree) -> NodeId {
+        let offset = self.nodes.len();
+        let (size, allocated, files, partial, modified) = (
+            subtree.nodes[0].size_bytes,
+            subtree.nodes[0].allocated_bytes,
+            subtree.nodes[0].file_count,
+            subtree.nodes[0].partial,
+            subtree.nodes[0].modified,
+        );
+
+        for (index, mut node) in subtree.nodes.into_iter().enumerate() {
+            node.parent = if index == 0 {
+                Some(parent)
+            } else {
+                node.parent.map(|p| p + offset)
+            };
+            for child in &mut node.children {
+                *child += offset;
+            }
+            self.nodes.push(node);
+        }
+        self.excluded.merge(&subtree.excluded);
+        self.cached_directories += subtree.cached_directories;
+        self.hard_links
+            .extend(subtree.hard_links.into_iter().map(|link| HardLink {
+                node: link.node + offset,
+                resolved_below: link.resolved_below.map(|root| root + offset),
+                ..link
+            }));
+
+        // The subtree root was named after its full path; keep only the file name
+        if let Some(name) = subtree.root_path.file_name() {
+            self.nodes[offset].name = name.to_os_string();
+        }
+        self.nodes[parent].children.push(offset);
+
+        let mut ancestor = Some(parent);
+        while let Some(id) = ancestor {
+            let node = &mut self.nodes[id];
+            node.size_bytes = node.size_bytes.saturating_add(size);
+            node.allocated_bytes = node.allocated_bytes.saturating_add(allocated);
+            node.file_count = node.file_count.saturating_add(files);
+            node.partial |= partial;
+            node.modified = node.modified.max(modified);
+            ancestor = node.parent;
+        }
+
+        offset
+    }
+
+    /// Rolls sizes, file counts, partial flags and modification times up to every
+    /// ancestor and sorts children by size.
+    ///
+    /// Nodes are always appended after their parent, so a single reverse pass over
+    /// the arena visits every child before the directory that contains it.
+    pub fn finalize(&mut self) {
+        for id in (0..self.nodes.len()).rev() {
+            let node = &mut self.nodes[id];
+            node.partial |= !node.errors.is_empty();
+
+            let (size, allocated, files, partial, modified) = (
+                node.size_bytes,
+                node.allocated_bytes,
+                node.file_count,
+                node.partial,
+                node.modified,
+            );
+            if let Some(parent) = node.parent {
+                let parent = &mut self.nodes[parent];
+                parent.size_bytes = parent.size_bytes.saturating_add(size);
+                parent.allocated_bytes = parent.allocated_bytes.saturating_add(allocated);
+                parent.file_count = parent.file_count.saturating_add(files);
+                parent.partial |= partial;
+                parent.modified = parent.modified.max(modified);
+            }
+        }
+        self.sort_children_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    }
+
+    /// Counts every hard-linked inode only once per directory and records shared bytes.
+    ///
+    /// Runs after sizes are rolled up. A directory that contains several links to the
+    /// same inode is charged for it once. Any node that holds some, but not all, of an
+    /// inode's links reports the inode as shared: deleting that node alone leaves the
+    /// data reachable elsewhere. Links that live outside the scanned tree make the
+    /// inode shared everywhere.
+    ///
+    /// Subtrees may be resolved on their own before they are grafted; resolving the
+    /// combined tree then only corrects the nodes above them. Resolving a tree twice
+    /// changes nothing.
+    pub fn resolve_hard_links(&mut self) {
+        let mut groups: HashMap<FileId, (u64, Vec<HardLink>)> = HashMap::new();
+        for link in std::mem::take(&mut self.hard_links) {
+            groups
+                .entry(link.file_id)
+                .or_insert_with(|| (link.link_count, Vec::new()))
+                .1
+                .push(link);
+        }
+
+        for (link_count, links) in groups.into_values() {
+            let (size, allocated) = (
+                self.nodes[links[0].node].size_bytes,
+                self.nodes[links[0].node].allocated_bytes,
+            );
+
+            // How many of this inode's links sit at or below each node, and how many
+            // times the node's totals currently include the inode
+            let mut links_below: HashMap<NodeId, (u64, u64)> = HashMap::new();
+            let mut resolved_roots = HashSet::new();
+            for link in &links {
+                // Nodes inside an already resolved subtree are correct; above it the
+                // subtree contributed the inode once, however many links it holds
+                let (start, charged) = match link.resolved_below {
+                    Some(root) => (self.nodes[root].parent, resolved_roots.insert(root)),
+                    None => (Some(link.node), true),
+                };
+                let mut current = start;
+                while let Some(id) = current {
+                    let (count, times_charged) = links_below.entry(id).or_default();
+                    *count += 1;
+                    *times_charged += u64::from(charged);
+                    current = self.nodes[id].parent;
+                }
+            }
+
+            for (id, (count, times_charged)) in links_below {
+                let node = &mut self.nodes[id];
+                let duplicates = times_charged.saturating_sub(1);
+                node.size_bytes = node.size_bytes.saturating_sub(size * duplicates);
+                node.allocated_bytes = node.allocated_bytes.saturating_sub(allocated * duplicates);
+                if count < link_count {
+                    node.shared_bytes = node.shared_bytes.saturating_add(size);
+                    node.shared_allocated_bytes =
+                        node.shared_allocated_bytes.saturating_add(allocated);
+                }
+            }
+
+            let root = self.root();
+            self.hard_links
+                .extend(links.into_iter().map(|link| HardLink {
+                    resolved_below: Some(root),
+                    ..link
+                }));
+        }
+    }
+
+    /// Re-sorts every directory's children largest first by the size `mode` selects.
+    pub fn sort_by_size(&mut self, mode: SizeMode) {
+        self.sort_children_by(|a, b| b.size(mode).cmp(&a.size(mode)));
+    }
+
+    /// Re-sorts the children of every directory without touching the disk.
+    pub fn sort_children_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&TreeNode, &TreeNode) -> Ordering,
+    {
+        for id in 0..self.nodes.len() {
+            self.sort_node_children(id, &mut compare);
+        }
+    }
+
+    /// Re-sorts the direct children of a single node.
+    fn sort_node_children<F>(&mut self, id: NodeId, mut compare: F)
+    where
+        F: FnMut(&TreeNode, &TreeNode) -> Ordering,
+    {
+        let mut children = std::mem::take(&mut self.nodes[id].children);
+        children.sort_by(|&a, &b| compare(&self.nodes[a], &self.nodes[b]));
+        self.nodes[id].children = children;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DirectoryCache;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// Looks up a node by path relative to the root, for assertions.
+    fn find(tree: &DirTree, relative: &str) -> NodeId {
+        let mut current = tree.root();
+        for name in relative.split('/') {
+            current = *tree
+                .children(current)
+                .iter()
+                .find(|&&child| tree.node(child).name == name)
+                .unwrap();
+        }
+        current
+    }
+
+    fn create_test_structure() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+
+        File::create(base.join("top.bin"))
+            .unwrap()
+            .write_all(&[b'x'; 300])
+            .unwrap();
+        fs::create_dir_all(base.join("a/b")).unwrap();
+        File::create(base.join("a/one.txt"))
+            .unwrap()
+            .write_all(&[b'y'; 100])
+            .unwrap();
+        File::create(base.join("a/b/two.txt"))
+            .unwrap()
+            .write_all(&[b'z'; 1000])
+            .unwrap();
+        fs::create_dir(base.join("empty")).unwrap();
+
+        temp_dir
+    }
+
+    #[test]
+    fn test_scan_builds_full_tree() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(temp_dir.path());
+
+        let root = tree.node(tree.root());
+        assert_eq!(root.size_bytes, 1400);
+        assert_eq!(root.file_count, 3);
+        assert_eq!(root.children.len(), 3);
+
+        let a = find(&tree, "a");
+        assert_eq!(tree.node(a).size_bytes, 1100);
+        assert_eq!(tree.node(a).file_count, 2);
+
+        let b = find(&tree, "a/b");
+        assert_eq!(tree.node(b).parent, Some(a));
+        assert_eq!(tree.path(b), temp_dir.path().join("a/b"));
+    }
+
+    #[test]
+    fn test_children_sorted_by_size() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(temp_dir.path());
+
+        let sizes: Vec<u64> = tree
+            .child_entries(tree.root())
+            .iter()
+            .map(|e| e.size_bytes)
+            .collect();
+        assert_eq!(sizes, vec![1100, 300, 0]);
+    }
+
+    #[test]
+    fn test_resort_without_rescan() {
+        let temp_dir = create_test_structure();
+        let mut tree = DirTree::scan(temp_dir.path());
+
+        tree.sort_children_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<OsString> = tree
+            .children(tree.root())
+            .iter()
+            .map(|&id| tree.node(id).name.clone())
+            .collect();
+        assert_eq!(names, vec!["a", "empty", "top.bin"]);
+    }
+
+    #[test]
+    fn test_graft_updates_ancestors() {
+        let temp_dir = create_test_structure();
+        let mut tree = DirTree::new(temp_dir.path().to_path_buf());
+        let subtree = DirTree::scan(&temp_dir.path().join("a"));
+
+        let a = tree.graft(tree.root(), subtree);
+
+        assert_eq!(tree.node(a).name, "a");
+        assert_eq!(tree.node(tree.root()).size_bytes, 1100);
+        let b = find(&tree, "a/b");
+        assert_eq!(tree.node(b).size_bytes, 1000);
+        assert_eq!(tree.entry(b).path, temp_dir.path().join("a/b"));
+    }
+
+    #[test]
+    fn test_scan_single_file() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(&temp_dir.path().join("top.bin"));
+
+        assert!(tree.children(tree.root()).is_empty());
+        assert!(!tree.node(tree.root()).is_directory);
+        assert_eq!(tree.node(tree.root()).size_bytes, 300);
+    }
+
+    #[test]
+    fn test_allocated_sizes_roll_up() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(temp_dir.path());
+
+        let a = find(&tree, "a");
+        let b = find(&tree, "a/b");
+        let one = find(&tree, "a/one.txt");
+        assert_eq!(
+            tree.node(a).allocated_bytes,
+            tree.node(b).allocated_bytes
+                + tree.node(one).allocated_bytes
+                + PlatformUtils::allocated_size(&fs::metadata(temp_dir.path().join("a")).unwrap())
+        );
+        assert_eq!(tree.entry(a).allocated_bytes, tree.node(a).allocated_bytes);
+    }
+
+    #[test]
+    fn test_sort_by_allocated_size() {
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let root = tree.root();
+        let sparse = tree.push_child(root, "sparse.img".into(), false, 1_000_000);
+        let dense = tree.push_child(root, "dense.bin".into(), false, 5000);
+        tree.nodes[sparse].allocated_bytes = 4096;
+        tree.finalize();
+
+        assert_eq!(tree.children(root), &[sparse, dense]);
+        tree.sort_by_size(SizeMode::Allocated);
+        assert_eq!(tree.children(root), &[dense, sparse]);
+        assert_eq!(tree.node(root).allocated_bytes, 9096);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_counted_once() {
+        let temp_dir = create_test_structure();
+        let base = temp_dir.path();
+        fs::create_dir(base.join("c")).unwrap();
+        // a/b/two.txt (1000 bytes) is also linked from c/ and from a/
+        fs::hard_link(base.join("a/b/two.txt"), base.join("c/copy.txt")).unwrap();
+        fs::hard_link(base.join("a/b/two.txt"), base.join("a/again.txt")).unwrap();
+
+        let mut tree = DirTree::scan(base);
+        tree.resolve_hard_links();
+
+        // The root holds every link, so the inode is counted once and not shared
+        let root = tree.node(tree.root());
+        assert_eq!(root.size_bytes, 1400);
+        assert_eq!(root.shared_bytes, 0);
+
+        // a/ holds two of three links: counted once, but shared with c/
+        let a = find(&tree, "a");
+        assert_eq!(tree.node(a).size_bytes, 1100);
+        assert_eq!(tree.node(a).shared_bytes, 1000);
+
+        let c = find(&tree, "c");
+        assert_eq!(tree.node(c).size_bytes, 1000);
+        assert_eq!(tree.entry(c).shared_bytes, 1000);
+
+        // Files that are not hard-linked are never shared
+        assert_eq!(tree.node(find(&tree, "top.bin")).shared_bytes, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_across_grafted_subtrees() {
+        let temp_dir = create_test_structure();
+        let base = temp_dir.path();
+        fs::create_dir(base.join("c")).unwrap();
+        fs::hard_link(base.join("top.bin"), base.join("c/top-link.bin")).unwrap();
+
+        let mut tree = DirTree::new(base.to_path_buf());
+        let root = tree.root();
+        tree.graft(root, DirTree::scan(&base.join("top.bin")));
+        tree.graft(root, DirTree::scan(&base.join("c")));
+        tree.resolve_hard_links();
+
+        assert_eq!(tree.node(root).size_bytes, 300);
+        assert_eq!(tree.node(root).shared_bytes, 0);
+        assert_eq!(tree.node(find(&tree, "c")).shared_bytes, 300);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_resolved_before_grafting() {
+        let temp_dir = create_test_structure();
+        let base = temp_dir.path();
+        fs::create_dir(base.join("c")).unwrap();
+        fs::hard_link(base.join("a/b/two.txt"), base.join("c/copy.txt")).unwrap();
+        fs::hard_link(base.join("a/b/two.txt"), base.join("a/again.txt")).unwrap();
+
+        let mut tree = DirTree::new(base.to_path_buf());
+        let root = tree.root();
+        for name in ["a", "c", "top.bin"] {
+            let mut subtree = DirTree::scan(&base.join(name));
+            subtree.resolve_hard_links();
+            tree.graft(root, subtree);
+        }
+        tree.resolve_hard_links();
+        tree.resolve_hard_links();
+
+        // Same totals as resolving the whole tree at once
+        assert_eq!(tree.node(root).size_bytes, 1400);
+        assert_eq!(tree.node(root).shared_bytes, 0);
+        let a = find(&tree, "a");
+        assert_eq!(tree.node(a).size_bytes, 1100);
+        assert_eq!(tree.node(a).shared_bytes, 1000);
+        assert_eq!(tree.node(find(&tree, "c")).shared_bytes, 1000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_reuses_unchanged_directories() {
+        let temp_dir = create_test_structure();
+        let base = temp_dir.path();
+        let (_, listings) = DirTree::scan_with(
+            base,
+            WalkOptions {
+                cache: Some(&DirectoryCache::default()),
+                ..WalkOptions::default()
+            },
+        );
+        assert_eq!(listings.len(), 4);
+        let mut cache = DirectoryCache::default();
+        cache.extend(listings);
+
+        // Rewriting a file in place leaves its directory alone, so its listing is
+        // reused, but the file is looked at again since the cache's age is unknown;
+        // a new file changes its directory, which is read again
+        fs::write(base.join("a/b/two.txt"), [b'z'; 10]).unwrap();
+        fs::write(base.join("a/three.txt"), [b'w'; 50]).unwrap();
+        let options = WalkOptions {
+            cache: Some(&cache),
+            ..WalkOptions::default()
+        };
+        let (tree, _) = DirTree::scan_with(base, options);
+
+        assert_eq!(tree.node(find(&tree, "a/b/two.txt")).size_bytes, 10);
+        assert_eq!(tree.node(find(&tree, "a/three.txt")).size_bytes, 50);
+        assert_eq!(tree.cached_directories(), 3);
+        let fresh = DirTree::scan(base);
+        assert_eq!(fresh.node(fresh.root()).size_bytes, 460);
+        assert_eq!(tree.node(tree.root()).size_bytes, 460);
+        assert_eq!(fresh.cached_directories(), 0);
+    }
+
+    #[test]
+    fn test_entries_to_depth() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(temp_dir.path());
+
+        let shallow = tree.entries_to_depth(tree.root(), 1);
+        assert_eq!(shallow.len(), 3);
+        assert!(shallow.iter().all(|e| e.depth == 1));
+
+        let deep = tree.entries_to_depth(tree.root(), 3);
+        let paths: Vec<PathBuf> = deep
+            .iter()
+            .map(|e| e.path.strip_prefix(temp_dir.path()).unwrap().to_path_buf())
+            .collect();
+        let expected: Vec<PathBuf> = ["a", "a/b", "a/b/two.txt", "a/one.txt", "top.bin", "empty"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(paths, expected);
+        assert_eq!(deep[2].depth, 3);
+    }
+
+    #[test]
+    fn test_errors_mark_ancestors_partial() {
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let a = tree.push_child(tree.root(), "a".into(), true, 0);
+        let b = tree.push_child(a, "b".into(), true, 0);
+        let c = tree.push_child(tree.root(), "c".into(), true, 0);
+        tree.nodes[b]
+            .errors
+            .push("/data/a/b/x: Permission denied".into());
+        tree.finalize();
+
+        assert!(tree.node(tree.root()).partial);
+        assert!(tree.node(a).partial);
+        assert!(tree.entry(b).is_partial);
+        assert!(!tree.node(c).partial);
+    }
+
+    #[test]
+    fn test_modified_rolls_up_newest_time() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut tree = DirTree::new(PathBuf::from("/data"));
+        let a = tree.push_child(tree.root(), "a".into(), true, 0);
+        let old = tree.push_child(a, "old".into(), false, 1);
+        let new = tree.push_child(a, "new".into(), false, 1);
+        let c = tree.push_child(tree.root(), "c".into(), true, 0);
+        tree.nodes[old].modified = Some(UNIX_EPOCH + Duration::from_secs(100));
+        tree.nodes[new].modified = Some(UNIX_EPOCH + Duration::from_secs(500));
+        tree.finalize();
+
+        let newest = Some(UNIX_EPOCH + Duration::from_secs(500));
+        assert_eq!(tree.node(a).modified, newest);
+        assert_eq!(tree.entry(tree.root()).modified, newest);
+        assert_eq!(tree.node(c).modified, None);
+    }
+
+    #[test]
+    fn test_scan_records_modification_times() {
+        let temp_dir = create_test_structure();
+        let tree = DirTree::scan(temp_dir.path());
+        let file = find(&tree, "a/b/two.txt");
+
+        let expected = fs::metadata(temp_dir.path().join("a/b/two.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(tree.node(file).modified, Some(expected));
+        assert!(tree.node(tree.root()).modified >= Some(expected));
+    }
+}
@@ -0,0 +1,532 @@
+//! # Full-Screen Browser Module
+//!
+//! An ncdu-style, full-screen terminal navigator built on `ratatui` and `crossterm`.
+//! It lets the user drill into directories, walk back up, and mark entries across
+//! different levels before handing the marked set to the regular deletion pipeline.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Key Features
+//!
+//! - **Drill-Down Navigation**: Enter directories and go back up without re-running the binary
+//! - **No Re-Walks**: Every level is read from the in-memory [`DirTree`] built by the scan
+//! - **Cross-Level Marking**: Marks survive navigation and are collected into one selection
+//! - **Nested Mark Pruning**: Entries inside a marked directory are not deleted twice
+//! - **Terminal Safety**: Raw mode and the alternate screen are always restored on exit
+//!
+//! ## Key Bindings
+//!
+//! | Key                      | Action                                  |
+//! |--------------------------|-----------------------------------------|
+//! | `↑`/`k`, `↓`/`j`         | Move the cursor                         |
+//! | `PgUp`, `PgDn`           | Move the cursor by a page               |
+//! | `Home`/`g`, `End`/`G`    | Jump to the first / last entry          |
+//! | `Enter`/`→`/`l`          | Open the selected directory             |
+//! | `←`/`h`/`Backspace`      | Go back to the parent directory         |
+//! | `Space`                  | Mark / unmark the selected entry        |
+//! | `d`                      | Finish and delete the marked entries    |
+//! | `q`/`Esc`                | Quit without selecting anything         |
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! let tree = analyzer.analyze_tree(&root).await?;
+//! let marked = tui::browse(&tree, |entries| entries)?;
+//! let (valid, _denied) = FileManager::split_denied(manager.validate_entries(&marked)?);
+//! ```
+
+use crate::analyzer::{self, DirectoryEntry, SizeMode};
+use crate::tree::{DirTree, NodeId};
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Stdout};
+use std::path::{Path, PathBuf};
+
+// Constants for the browser layout
+const BAR_WIDTH: usize = 10;
+const PAGE_SIZE: usize = 10;
+
+/// Filter applied to the children of every level before they are listed.
+pub type LevelFilter<'a> = Box<dyn Fn(Vec<DirectoryEntry>) -> Vec<DirectoryEntry> + 'a>;
+
+/// One directory level on the navigation stack.
+#[derive(Debug, Clone)]
+struct Level {
+    /// Directory whose children are listed
+    path: PathBuf,
+    /// Visible children of `path`, sorted by size
+    entries: Vec<DirectoryEntry>,
+    /// Tree node of each visible child, parallel to `entries`
+    nodes: Vec<NodeId>,
+    /// Cursor position within `entries`
+    selected: usize,
+}
+
+/// Navigation and marking state of the browser, independent of the terminal.
+///
+/// Keeping the state free of any terminal handling makes every key binding
+/// testable without a TTY; the event loop in [`browse`] only translates key
+/// presses into calls on this type and renders it.
+pub struct BrowserState<'a> {
+    tree: &'a DirTree,
+    size_mode: SizeMode,
+    filter: LevelFilter<'a>,
+    levels: Vec<Level>,
+    marked: BTreeMap<PathBuf, DirectoryEntry>,
+}
+
+impl<'a> BrowserState<'a> {
+    /// Creates a browser positioned at the root of `tree`, showing sizes per `size_mode`.
+    pub fn new(tree: &'a DirTree, size_mode: SizeMode, filter: LevelFilter<'a>) -> Self {
+        let mut state = Self {
+            tree,
+            size_mode,
+            filter,
+            levels: Vec::new(),
+            marked: BTreeMap::new(),
+        };
+        let root = state.level(tree.root());
+        state.levels.push(root);
+        state
+    }
+
+    /// Builds the listing for `node`, keeping track of which tree node each row is.
+    fn level(&self, node: NodeId) -> Level {
+        let children = self.tree.children(node);
+        let entries = self.tree.child_entries(node);
+        let ids: HashMap<PathBuf, NodeId> = entries
+            .iter()
+            .map(|entry| entry.path.clone())
+            .zip(children.iter().copied())
+            .collect();
+
+        let entries = (self.filter)(entries);
+        let nodes = entries.iter().map(|entry| ids[&entry.path]).collect();
+        Level {
+            path: self.tree.path(node),
+            entries,
+            nodes,
+            selected: 0,
+        }
+    }
+
+    fn current(&self) -> &Level {
+        self.levels.last().expect("browser always has a root level")
+    }
+
+    fn current_mut(&mut self) -> &mut Level {
+        self.levels
+            .last_mut()
+            .expect("browser always has a root level")
+    }
+
+    /// Directory currently being listed.
+    pub fn current_path(&self) -> &Path {
+        &self.current().path
+    }
+
+    /// Entries of the current level.
+    pub fn entries(&self) -> &[DirectoryEntry] {
+        &self.current().entries
+    }
+
+    /// Cursor position within the current level.
+    pub fn selected_index(&self) -> usize {
+        self.current().selected
+    }
+
+    /// Entry under the cursor, if the current level is not empty.
+    pub fn selected_entry(&self) -> Option<&DirectoryEntry> {
+        let level = self.current();
+        level.entries.get(level.selected)
+    }
+
+    /// Whether the cursor is at the scan root (cannot go further up).
+    pub fn at_root(&self) -> bool {
+        self.levels.len() == 1
+    }
+
+    /// Moves the cursor by `delta` rows, clamping to the list bounds.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let level = self.current_mut();
+        if level.entries.is_empty() {
+            level.selected = 0;
+            return;
+        }
+        let last = level.entries.len() - 1;
+        level.selected = level.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Moves the cursor to the first entry.
+    pub fn move_to_first(&mut self) {
+        self.current_mut().selected = 0;
+    }
+
+    /// Moves the cursor to the last entry.
+    pub fn move_to_last(&mut self) {
+        let level = self.current_mut();
+        level.selected = level.entries.len().saturating_sub(1);
+    }
+
+    /// Marks or unmarks the entry under the cursor.
+    pub fn toggle_mark(&mut self) {
+        let Some(entry) = self.selected_entry().cloned() else {
+            return;
+        };
+        if self.marked.remove(&entry.path).is_none() {
+            self.marked.insert(entry.path.clone(), entry);
+        }
+    }
+
+    /// Whether `path` is currently marked.
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.marked.contains_key(path)
+    }
+
+    /// Number of marked entries and their combined size in bytes.
+    pub fn marked_totals(&self) -> (usize, u64) {
+        let entries = self.marked_entries();
+        (
+            entries.len(),
+            entries.iter().map(|e| e.size(self.size_mode)).sum(),
+        )
+    }
+
+    /// Opens the directory under the cursor. Returns `false` if it is not a directory.
+    pub fn descend(&mut self) -> bool {
+        let level = self.current();
+        let Some(&node) = level.nodes.get(level.selected) else {
+            return false;
+        };
+        if !self.tree.node(node).is_directory {
+            return false;
+        }
+        let next = self.level(node);
+        self.levels.push(next);
+        true
+    }
+
+    /// Returns to the parent level. Returns `false` when already at the root.
+    pub fn pop_level(&mut self) -> bool {
+        if self.at_root() {
+            return false;
+        }
+        self.levels.pop();
+        true
+    }
+
+    /// Marked entries with anything nested inside another marked directory removed.
+    ///
+    /// Deleting a directory already removes its contents, so keeping both would
+    /// only produce spurious "not found" failures and double-count freed space.
+    pub fn marked_entries(&self) -> Vec<DirectoryEntry> {
+        let marked: Vec<DirectoryEntry> = self.marked.values().cloned().collect();
+        let mut result = analyzer::without_nested(&marked);
+        result.sort_by_key(|entry| std::cmp::Reverse(entry.size(self.size_mode)));
+        result
+    }
+}
+
+/// Restores the terminal when dropped, even if the browser returns early or panics.
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        if let Err(e) = execute!(stdout, EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(e.into());
+        }
+        let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+/// Runs the full-screen browser and returns the entries the user marked for deletion.
+///
+/// Every level is read from `tree` and passed through `filter`, so the same size
+/// and type filters as the table view apply at every level. Sizes and bars follow
+/// `size_mode`. An empty vector is returned when the user quits without
+/// confirming a selection.
+pub fn browse<'a, F>(
+    tree: &'a DirTree,
+    size_mode: SizeMode,
+    filter: F,
+) -> Result<Vec<DirectoryEntry>>
+where
+    F: Fn(Vec<DirectoryEntry>) -> Vec<DirectoryEntry> + 'a,
+{
+    let mut state = BrowserState::new(tree, size_mode, Box::new(filter));
+    let mut guard = TerminalGuard::new()?;
+
+    loop {
+        guard.terminal.draw(|frame| draw(frame, &state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(vec![]),
+            KeyCode::Char('d') => return Ok(state.marked_entries()),
+            KeyCode::Up | KeyCode::Char('k') => state.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => state.move_cursor(1),
+            KeyCode::PageUp => state.move_cursor(-(PAGE_SIZE as isize)),
+            KeyCode::PageDown => state.move_cursor(PAGE_SIZE as isize),
+            KeyCode::Home | KeyCode::Char('g') => state.move_to_first(),
+            KeyCode::End | KeyCode::Char('G') => state.move_to_last(),
+            KeyCode::Char(' ') => {
+                state.toggle_mark();
+                state.move_cursor(1);
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => {
+                state.pop_level();
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                state.descend();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Renders the header, the entry list and the footer for the current state.
+fn draw(frame: &mut Frame, state: &BrowserState) {
+    let [header_area, list_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let level_total: u64 = state
+        .entries()
+        .iter()
+        .map(|e| e.size(state.size_mode))
+        .sum();
+    let header = format!(
+        " 📁 {}  ({})",
+        state.current_path().display(),
+        humansize::format_size(level_total, humansize::DECIMAL)
+    );
+    frame.render_widget(
+        Paragraph::new(header).style(Style::default().add_modifier(Modifier::BOLD)),
+        header_area,
+    );
+
+    let largest = state
+        .entries()
+        .iter()
+        .map(|e| e.size(state.size_mode))
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = state
+        .entries()
+        .iter()
+        .map(|entry| ListItem::new(Line::from(format_row(entry, largest, state))))
+        .collect();
+
+    let list = if items.is_empty() {
+        List::new(vec![ListItem::new("  (empty)")])
+    } else {
+        List::new(items)
+    }
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut list_state = ListState::default();
+    if !state.entries().is_empty() {
+        list_state.select(Some(state.selected_index()));
+    }
+    frame.render_stateful_widget(list, list_area, &mut list_state);
+
+    let (count, bytes) = state.marked_totals();
+    let footer = format!(
+        " {} marked ({})  │  ↑↓ move  ⏎ open  ← back  ␣ mark  d delete marked  q quit",
+        count,
+        humansize::format_size(bytes, humansize::DECIMAL)
+    );
+    frame.render_widget(
+        Paragraph::new(footer).style(Style::default().add_modifier(Modifier::DIM)),
+        footer_area,
+    );
+}
+
+/// Formats a single list row: mark, partial flag, size, relative size bar, type and name.
+fn format_row(entry: &DirectoryEntry, largest: u64, state: &BrowserState) -> String {
+    let mark = if state.is_marked(&entry.path) {
+        '*'
+    } else {
+        ' '
+    };
+    // Like ncdu, `!` flags sizes that are a lower bound because of read errors
+    // and `H` flags hard-linked data that is also reachable from elsewhere
+    let flag = if entry.is_partial {
+        '!'
+    } else if entry.shared_bytes > 0 {
+        'H'
+    } else {
+        ' '
+    };
+    let size = entry.size(state.size_mode);
+    let filled = if largest == 0 {
+        0
+    } else {
+        ((size as f64 / largest as f64) * BAR_WIDTH as f64).round() as usize
+    };
+    let bar = format!("{}{}", "#".repeat(filled), " ".repeat(BAR_WIDTH - filled));
+    let name = entry
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_else(|| entry.path.to_string_lossy());
+    let (file_type, suffix) = if entry.is_directory {
+        ("DIR ", "/")
+    } else {
+        ("FILE", "")
+    };
+    format!(
+        "{}{} {:>10} [{}] {} {}{}",
+        mark,
+        flag,
+        humansize::format_size(size, humansize::DECIMAL),
+        bar,
+        file_type,
+        name,
+        suffix
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tree() -> DirTree {
+        let mut tree = DirTree::new(PathBuf::from("/root"));
+        let root = tree.root();
+        let big = tree.push_child(root, "big".into(), true, 0);
+        tree.push_child(big, "inner".into(), false, 3000);
+        tree.push_child(root, "file.bin".into(), false, 2000);
+        let small = tree.push_child(root, "small".into(), true, 0);
+        tree.push_child(small, "tiny".into(), false, 100);
+        tree.finalize();
+        tree
+    }
+
+    fn unfiltered<'a>() -> LevelFilter<'a> {
+        Box::new(|entries| entries)
+    }
+
+    #[test]
+    fn test_cursor_movement_is_clamped() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(&tree, SizeMode::Apparent, unfiltered());
+
+        state.move_cursor(-1);
+        assert_eq!(state.selected_index(), 0);
+
+        state.move_cursor(PAGE_SIZE as isize);
+        assert_eq!(state.selected_index(), 2);
+
+        state.move_to_first();
+        assert_eq!(state.selected_index(), 0);
+        state.move_to_last();
+        assert_eq!(state.selected_index(), 2);
+    }
+
+    #[test]
+    fn test_descend_and_return_preserves_marks() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(&tree, SizeMode::Apparent, unfiltered());
+        state.move_cursor(1);
+        state.toggle_mark(); // file.bin
+
+        state.move_to_first();
+        assert!(state.descend());
+        assert_eq!(state.current_path(), Path::new("/root/big"));
+        assert_eq!(state.entries()[0].path, PathBuf::from("/root/big/inner"));
+        state.toggle_mark(); // inner
+
+        assert!(state.pop_level());
+        assert!(!state.pop_level());
+
+        let marked = state.marked_entries();
+        assert_eq!(marked.len(), 2);
+        assert_eq!(state.marked_totals(), (2, 5000));
+    }
+
+    #[test]
+    fn test_files_cannot_be_opened() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(&tree, SizeMode::Apparent, unfiltered());
+        state.move_cursor(1);
+        assert!(!state.descend());
+        assert!(state.at_root());
+    }
+
+    #[test]
+    fn test_marked_entries_prune_nested_paths() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(&tree, SizeMode::Apparent, unfiltered());
+        assert!(state.descend());
+        state.toggle_mark(); // inner
+        state.pop_level();
+        state.toggle_mark(); // big, which contains inner
+
+        let marked = state.marked_entries();
+        assert_eq!(marked.len(), 1);
+        assert_eq!(marked[0].path, PathBuf::from("/root/big"));
+    }
+
+    #[test]
+    fn test_filter_applies_at_every_level() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(
+            &tree,
+            SizeMode::Apparent,
+            Box::new(|mut entries: Vec<DirectoryEntry>| {
+                entries.retain(|e| e.size_bytes >= 1000);
+                entries
+            }),
+        );
+
+        assert_eq!(state.entries().len(), 2);
+        assert!(state.descend());
+        assert_eq!(state.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_toggle_mark_twice_unmarks() {
+        let tree = sample_tree();
+        let mut state = BrowserState::new(&tree, SizeMode::Apparent, unfiltered());
+        state.toggle_mark();
+        assert!(state.is_marked(Path::new("/root/big")));
+        state.toggle_mark();
+        assert!(!state.is_marked(Path::new("/root/big")));
+    }
+}
@@ -0,0 +1,281 @@
+//! # Parallel Directory Walker
+//!
+//! Reads a directory hierarchy with every directory as its own task on a rayon
+//! thread pool. Idle threads steal pending directories from busy ones, so a single
+//! huge subdirectory such as `node_modules` is spread over all threads instead of
+//! keeping one of them busy while the others wait.
+//!
+//! Copyright (c) 2025 @srcheesedev
+//! Licensed under the MIT License - see LICENSE file for details
+//!
+//! ## Output
+//!
+//! Directories finish in no particular order, so each one is identified by a slot
+//! number handed out when it is discovered; the root is slot 0. Every listed
+//! subdirectory names the slot holding its own contents, which lets
+//! [`DirTree`](crate::tree::DirTree) assemble the tree afterwards in one pass.
+//! The walk runs on the rayon pool it is called from, or on the global pool.
+
+use crate::cache::{CachedDirectory, CachedEntry, DirectoryCache};
+use crate::filter::{ExcludedTotals, PathFilter};
+use crate::platform::PlatformUtils;
+use crate::progress::ScanProgress;
+use rayon::Scope;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// What was found in one directory.
+#[derive(Debug)]
+pub struct ReadDirectory {
+    /// Slot of this directory; its position in the result of [`read_tree`]
+    pub slot: usize,
+    /// Entries in listing order; subdirectories come with the slot of their contents
+    pub entries: Vec<(CachedEntry, Option<usize>)>,
+    /// Errors hit while reading this directory or looking at its entries
+    pub errors: Vec<String>,
+    /// Entries left out by the filter
+    pub excluded: ExcludedTotals,
+    /// Listing to cache for the next scan, if the directory was read completely
+    pub listing: Option<CachedDirectory>,
+    /// Whether the listing was taken from the cache instead of reading the directory
+    pub from_cache: bool,
+}
+
+/// Settings of one walk.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WalkOptions<'a> {
+    /// Listings from the last scan; unchanged directories are not read again
+    pub cache: Option<&'a DirectoryCache>,
+    /// Counters to update as directories are read
+    pub progress: Option<&'a ScanProgress>,
+    /// Entries to leave out; excluded directories are not read
+    pub filter: Option<&'a PathFilter>,
+    /// Device to stay on; directories on other devices are listed but not read
+    pub device: Option<u64>,
+}
+
+impl WalkOptions<'_> {
+    /// Whether the directory described by `metadata` is on the device to stay on
+    pub fn stays_on_device(&self, metadata: &fs::Metadata) -> bool {
+        self.device.is_none_or(|device| {
+            PlatformUtils::file_id(metadata).is_none_or(|id| id.device == device)
+        })
+    }
+}
+
+/// State shared by the tasks of one walk.
+struct Walker<'a> {
+    options: WalkOptions<'a>,
+    next_slot: AtomicUsize,
+    found: Mutex<Vec<ReadDirectory>>,
+}
+
+/// Reads the directory `root`, described by `metadata`, and everything below it.
+///
+/// Returns one [`ReadDirectory`] per directory, indexed by slot.
+pub fn read_tree(
+    root: &Path,
+    metadata: fs::Metadata,
+    options: WalkOptions<'_>,
+) -> Vec<ReadDirectory> {
+    let walker = Walker {
+        options,
+        next_slot: AtomicUsize::new(1),
+        found: Mutex::new(Vec::new()),
+    };
+    if let Some(progress) = options.progress {
+        progress.directories_found(1);
+    }
+    rayon::scope(|scope| walker.read(scope, 0, root.to_path_buf(), metadata));
+
+    let mut found = walker
+        .found
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    found.sort_unstable_by_key(|directory| directory.slot);
+    found
+}
+
+impl<'a> Walker<'a> {
+    /// Lists `dir` and spawns a task for each of its subdirectories.
+    fn read<'s>(&'s self, scope: &Scope<'s>, slot: usize, dir: PathBuf, metadata: fs::Metadata)
+    where
+        'a: 's,
+    {
+        let progress = self.options.progress;
+        if let Some(progress) = progress {
+            progress.directory_started(&dir);
+        }
+        let mut errors = Vec::new();
+        let cache = self.options.cache;
+        let stamp = cache.and_then(|_| PlatformUtils::dir_stamp(&metadata));
+        let cached = cache
+            .zip(stamp.as_ref())
+            .and_then(|(cache, stamp)| Some((cache, cache.get(&dir, stamp)?)));
+        let from_cache = cached.is_some();
+        let (listed, complete) = match cached {
+            Some((cache, listing)) => (cache.fresh_entries(listing), true),
+            None => read_listing(&dir, &mut errors),
+        };
+
+        let mut entries = Vec::with_capacity(listed.len());
+        let mut excluded = ExcludedTotals::default();
+        for entry in &listed {
+            if let Some(filter) = self.options.filter {
+                if filter.excludes(&dir.join(&entry.name), entry.is_directory) {
+                    excluded.add(entry);
+                    continue;
+                }
+            }
+            if !entry.is_directory {
+                entries.push((entry.clone(), None));
+                continue;
+            }
+            let child_path = dir.join(&entry.name);
+            match child_path.symlink_metadata() {
+                Ok(meta) if meta.is_dir() => {
+                    let child = CachedEntry {
+                        allocated_bytes: PlatformUtils::allocated_size(&meta),
+                        modified: meta.modified().ok(),
+                        ..entry.clone()
+                    };
+                    // Another filesystem is kept as an empty directory
+                    if !self.options.stays_on_device(&meta) {
+                        entries.push((child, None));
+                        continue;
+                    }
+                    let child_slot = self.next_slot.fetch_add(1, Ordering::Relaxed);
+                    entries.push((child, Some(child_slot)));
+                    if let Some(progress) = progress {
+                        progress.directories_found(1);
+                    }
+                    scope.spawn(move |scope| self.read(scope, child_slot, child_path, meta));
+                }
+                // Replaced by something else since the listing was read
+                Ok(meta) => entries.push((CachedEntry::file(entry.name.clone(), &meta), None)),
+                Err(e) => {
+                    errors.push(format!("{}: {}", child_path.display(), e));
+                    entries.push((entry.clone(), None));
+                }
+            }
+        }
+
+        if let Some(progress) = progress {
+            let files = entries.iter().filter(|(entry, _)| !entry.is_directory);
+            let (count, bytes) = files.fold((0, 0), |(count, bytes), (entry, _)| {
+                (count + 1, bytes + entry.size_bytes)
+            });
+            progress.directory_finished(count, bytes, errors.len() as u64);
+        }
+
+        let listing = stamp.filter(|_| complete).map(|stamp| CachedDirectory {
+            path: dir,
+            stamp,
+            entries: listed,
+        });
+        self.found
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(ReadDirectory {
+                slot,
+                entries,
+                errors,
+                excluded,
+                listing,
+                from_cache,
+            });
+    }
+}
+
+/// Reads the entries of `dir` from disk, adding any errors to `errors`. Also
+/// returns whether every entry could be read.
+fn read_listing(dir: &Path, errors: &mut Vec<String>) -> (Vec<CachedEntry>, bool) {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) => {
+            errors.push(format!("{}: {}", dir.display(), e));
+            return (Vec::new(), false);
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut complete = true;
+    for dir_entry in read_dir {
+        let dir_entry = match dir_entry {
+            Ok(dir_entry) => dir_entry,
+            Err(e) => {
+                errors.push(format!("{}: {}", dir.display(), e));
+                complete = false;
+                continue;
+            }
+        };
+        let name = PathBuf::from(dir_entry.file_name());
+        // Directories are looked at when their own contents are read
+        let is_directory = dir_entry.file_type().is_ok_and(|kind| kind.is_dir());
+        if is_directory {
+            entries.push(CachedEntry::directory(name));
+            continue;
+        }
+        match dir_entry.metadata() {
+            Ok(meta) => entries.push(CachedEntry::file(name, &meta)),
+            Err(e) => {
+                errors.push(format!("{}: {}", dir_entry.path().display(), e));
+                complete = false;
+                entries.push(CachedEntry {
+                    name,
+                    is_directory: false,
+                    size_bytes: 0,
+                    allocated_bytes: 0,
+                    modified: None,
+                    hard_link: None,
+                });
+            }
+        }
+    }
+    (entries, complete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_slots_link_directories_to_their_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        for i in 0..20 {
+            fs::create_dir_all(base.join(format!("d{}/inner", i))).unwrap();
+            fs::write(base.join(format!("d{}/inner/file.txt", i)), [b'x'; 10]).unwrap();
+        }
+
+        let progress = ScanProgress::default();
+        let options = WalkOptions {
+            progress: Some(&progress),
+            ..WalkOptions::default()
+        };
+        let found = read_tree(base, fs::metadata(base).unwrap(), options);
+        assert_eq!(found.len(), 41);
+        assert!(found.iter().enumerate().all(|(i, d)| d.slot == i));
+        assert!(found.iter().all(|d| d.listing.is_none()));
+
+        // Following the slots from the root reaches every file exactly once
+        let mut files = 0;
+        let mut pending = vec![0];
+        while let Some(slot) = pending.pop() {
+            for (_, child) in &found[slot].entries {
+                match child {
+                    Some(child) => pending.push(*child),
+                    None => files += 1,
+                }
+            }
+        }
+        assert_eq!(files, 20);
+
+        let counted = progress.snapshot();
+        assert_eq!((counted.files, counted.bytes), (20, 200));
+        assert_eq!(counted.directories_pending, 0);
+    }
+}
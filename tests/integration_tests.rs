@@ -41,7 +41,10 @@ fn create_integration_test_structure() -> Result<TempDir, Box<dyn std::error::Er
 /// Helper function to create a command for the binary
 fn get_test_command() -> Command {
     // Use the modern way to get cargo binary command with fallback support
-    Command::new(env!("CARGO_BIN_EXE_disk-cleaner"))
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_disk-cleaner"));
+    // Keep scan caches out of the user's home directory
+    cmd.env("XDG_CACHE_HOME", env!("CARGO_TARGET_TMPDIR"));
+    cmd
 }
 
 #[test]
@@ -102,6 +105,63 @@ fn test_depth_limiting() {
     assert!(stdout.contains("subdir"));
 }
 
+#[test]
+fn test_depth_does_not_truncate_sizes() {
+    let temp_dir = create_integration_test_structure().unwrap();
+    fs::create_dir_all(temp_dir.path().join("subdir/a/b")).unwrap();
+    let mut deep_file = File::create(temp_dir.path().join("subdir/a/b/deep.txt")).unwrap();
+    deep_file.write_all(&vec![b'D'; 3000]).unwrap(); // 3KB, three levels down
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path().to_str().unwrap())
+        .arg("--depth")
+        .arg("1");
+
+    cmd.timeout(std::time::Duration::from_secs(5));
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // subdir holds nested.txt (1KB) plus deep.txt (3KB) even though only one level is shown
+    let subdir_line = stdout.lines().find(|l| l.ends_with(" subdir")).unwrap();
+    assert!(subdir_line.trim_start().starts_with("4 kB"));
+    assert!(!stdout.contains("deep.txt"));
+}
+
+#[test]
+fn test_depth_shows_nested_entries() {
+    let temp_dir = create_integration_test_structure().unwrap();
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path().to_str().unwrap())
+        .arg("--depth")
+        .arg("2");
+
+    cmd.timeout(std::time::Duration::from_secs(5));
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("subdir"));
+    assert!(stdout.contains("  nested.txt"));
+}
+
+#[test]
+fn test_summary_shows_allocated_column() {
+    let temp_dir = create_integration_test_structure().unwrap();
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path().to_str().unwrap())
+        .arg("--size-mode")
+        .arg("allocated");
+
+    cmd.timeout(std::time::Duration::from_secs(5));
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("SIZE"));
+    assert!(stdout.contains("ON DISK"));
+    assert!(stdout.contains("large_file.txt"));
+}
+
 #[test]
 fn test_size_filtering() {
     let temp_dir = create_integration_test_structure().unwrap();
@@ -198,3 +258,538 @@ fn test_file_instead_of_directory() {
         .failure()
         .stderr(predicate::str::contains("is not a directory"));
 }
+
+#[test]
+fn test_restore_lists_and_restores_sessions() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let state_home = temp_dir.path().join("state");
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", &state_home).arg("restore");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No cleanup sessions recorded"));
+
+    // A session that moved one file to the trash
+    let original = temp_dir.path().join("work/build.log");
+    let trashed = temp_dir.path().join("Trash/files/build.log");
+    fs::create_dir_all(trashed.parent().unwrap())?;
+    fs::write(&trashed, b"log")?;
+    let sessions = state_home.join("disk-cleaner/sessions");
+    fs::create_dir_all(&sessions)?;
+    let session = format!(
+        r#"{{"id":"2025-01-02-03-04-05","started_at":1735786800,"entries":[
+            {{"original":{:?},"kind":"file","destination":{:?},"bytes":4096,"removed_at":1735786800}}
+        ]}}"#,
+        original.to_str().unwrap(),
+        trashed.to_str().unwrap()
+    );
+    fs::write(sessions.join("2025-01-02-03-04-05.json"), session)?;
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", &state_home).arg("restore");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-02-03-04-05"))
+        .stdout(predicate::str::contains("1 restorable"));
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", &state_home)
+        .args(["restore", "--last"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 of 1 items"));
+
+    assert_eq!(fs::read(&original)?, b"log");
+    assert!(!trashed.exists());
+    Ok(())
+}
+
+#[test]
+fn test_batch_mode_deletes_matching_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let state = TempDir::new()?;
+    let rules = state.path().join("rules.toml");
+    fs::write(
+        &rules,
+        "[[rule]]\nname = \"big text\"\npaths = [\"*.txt\"]\nmin_size = 1500\n",
+    )?;
+
+    // Without --yes nothing is touched
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .arg(temp_dir.path())
+        .arg("--rules")
+        .arg(&rules);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--yes"));
+    assert!(temp_dir.path().join("large_file.txt").exists());
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .arg(temp_dir.path())
+        .arg("--rules")
+        .arg(&rules)
+        .args(["--yes", "--permanent"]);
+    cmd.assert()
+        .code(0)
+        .stdout(predicate::str::contains("(big text)"));
+
+    assert!(!temp_dir.path().join("large_file.txt").exists());
+    assert!(!temp_dir.path().join("medium_file.txt").exists());
+    assert!(temp_dir.path().join("small_file.txt").exists());
+    assert!(temp_dir.path().join("subdir/nested.txt").exists());
+
+    // A second run finds nothing left to do
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .arg(temp_dir.path())
+        .arg("--rules")
+        .arg(&rules)
+        .args(["--yes", "--permanent"]);
+    cmd.assert().code(3);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_batch_mode_reports_partial_failure() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = create_integration_test_structure()?;
+    let state = TempDir::new()?;
+    let rules = state.path().join("rules.toml");
+    fs::write(&rules, "[[rule]]\nkind = \"file\"\nmin_size = 1500\n")?;
+    // A file can only be removed from a directory that can be written
+    let locked_dir = temp_dir.path().join("locked");
+    fs::create_dir(&locked_dir)?;
+    let locked = locked_dir.join("large_file.txt");
+    fs::write(&locked, vec![b'L'; 5000])?;
+    fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o555))?;
+    if fs::write(locked_dir.join("probe"), b"").is_ok() {
+        // Running with privileges that ignore permissions
+        return Ok(());
+    }
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .arg(temp_dir.path())
+        .arg("--rules")
+        .arg(&rules)
+        .args(["--yes", "--permanent"]);
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains("no write permission"));
+
+    assert!(locked.exists());
+    assert!(!temp_dir.path().join("medium_file.txt").exists());
+    fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[test]
+fn test_protected_paths_are_never_deleted() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let state = TempDir::new()?;
+    let rules = state.path().join("rules.toml");
+    fs::write(&rules, "[[rule]]\nkind = \"directory\"\n")?;
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .env("DISK_CLEANER_PROTECT", temp_dir.path().join("empty_dir"))
+        .arg(temp_dir.path())
+        .arg("--protect")
+        .arg(temp_dir.path().join("subdir/nested.txt"))
+        .arg("--rules")
+        .arg(&rules)
+        .args(["--yes", "--permanent"]);
+    cmd.assert()
+        .code(4)
+        .stdout(predicate::str::contains("it contains a protected path"))
+        .stdout(predicate::str::contains("it is a protected path"));
+
+    assert!(temp_dir.path().join("subdir/nested.txt").exists());
+    assert!(temp_dir.path().join("empty_dir").exists());
+    Ok(())
+}
+
+#[test]
+fn test_batch_dry_run_needs_no_confirmation() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let state = TempDir::new()?;
+    let rules = state.path().join("rules.toml");
+    fs::write(&rules, "[[rule]]\npaths = [\"subdir\"]\n")?;
+
+    let mut cmd = get_test_command();
+    cmd.env("XDG_STATE_HOME", state.path())
+        .arg(temp_dir.path())
+        .arg("--rules")
+        .arg(&rules)
+        .arg("--dry-run");
+    cmd.assert()
+        .code(0)
+        .stdout(predicate::str::contains("Would delete 1 items"));
+
+    assert!(temp_dir.path().join("subdir/nested.txt").exists());
+    Ok(())
+}
+
+#[test]
+fn test_json_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path())
+        .args(["--format", "json", "--depth", "2"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    // stdout holds nothing but the document
+    let json: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(json["root"], temp_dir.path().to_str().unwrap());
+    assert_eq!(json["depth"], 2);
+    assert_eq!(json["totals"]["apparent_bytes"], 8500);
+    assert_eq!(json["totals"]["file_count"], 4);
+    assert_eq!(json["errors"].as_array().unwrap().len(), 0);
+
+    let entries = json["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 6);
+    assert!(entries[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("large_file.txt"));
+    assert_eq!(entries[0]["size_bytes"], 5000);
+    assert!(entries
+        .iter()
+        .any(|e| e["path"].as_str().unwrap().ends_with("nested.txt") && e["depth"] == 2));
+    Ok(())
+}
+
+#[test]
+fn test_ndjson_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path())
+        .args(["--format", "ndjson", "--depth", "2", "--files-only"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(output)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let (summary, entries) = lines.split_last().unwrap();
+
+    assert_eq!(entries.len(), 4);
+    assert!(entries
+        .iter()
+        .all(|e| e["type"] == "entry" && e["is_directory"] == false));
+    assert_eq!(summary["type"], "summary");
+    assert_eq!(summary["totals"]["apparent_bytes"], 8500);
+    assert_eq!(summary["totals"]["entry_count"], 4);
+    Ok(())
+}
+
+#[test]
+fn test_csv_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path()).args(["--format", "csv"]);
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+
+    let rows: Vec<&str> = output.split_terminator("\r\n").collect();
+    assert_eq!(
+        rows[0],
+        "path,type,apparent_bytes,size_human,depth,modified,file_count"
+    );
+    assert_eq!(rows.len(), 6);
+    let large = rows[1].split(',').collect::<Vec<_>>();
+    assert!(large[0].ends_with("large_file.txt"));
+    assert_eq!(&large[1..5], ["file", "5000", "5 kB", "1"]);
+    assert_eq!(large[6], "1");
+    Ok(())
+}
+
+#[test]
+fn test_html_report() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let output_dir = TempDir::new()?;
+    let report = output_dir.path().join("usage.html");
+
+    let mut cmd = get_test_command();
+    cmd.arg("report")
+        .arg("--html")
+        .arg(&report)
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Report written to"));
+
+    let page = fs::read_to_string(&report)?;
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("large_file.txt"));
+    assert!(page.contains("\"extension\":\".txt\""));
+    Ok(())
+}
+
+#[test]
+fn test_ncdu_export_and_import() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let export_dir = TempDir::new()?;
+    let export = export_dir.path().join("scan.ncdu.json");
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path()).args(["--format", "ncdu"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let ncdu: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(ncdu[0], 1);
+    assert_eq!(ncdu[3][0]["name"], temp_dir.path().to_str().unwrap());
+    fs::write(&export, &output)?;
+
+    // Importing the export gives the same analysis without touching the directory
+    let mut cmd = get_test_command();
+    cmd.arg("--import")
+        .arg(&export)
+        .args(["--format", "json", "--depth", "2"]);
+    let imported = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&imported)?;
+    assert_eq!(json["root"], temp_dir.path().to_str().unwrap());
+    assert_eq!(json["totals"]["apparent_bytes"], 8500);
+    assert_eq!(json["totals"]["file_count"], 4);
+    assert_eq!(json["entries"].as_array().unwrap().len(), 6);
+    Ok(())
+}
+
+#[test]
+fn test_import_rejects_invalid_exports() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = get_test_command();
+    cmd.args(["--import", "-", "--format", "json"])
+        .write_stdin("{\"not\": \"ncdu\"}");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot import"));
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_and_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let snapshot_dir = TempDir::new()?;
+    let snapshot = snapshot_dir.path().join("before.json");
+
+    let mut cmd = get_test_command();
+    cmd.arg("snapshot")
+        .arg(temp_dir.path())
+        .arg("--output")
+        .arg(&snapshot);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Saved 6 entries"));
+
+    // Nothing changed yet
+    let mut cmd = get_test_command();
+    cmd.arg("diff").arg(&snapshot);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing changed"));
+
+    fs::write(temp_dir.path().join("subdir/nested.txt"), vec![b'N'; 4000])?;
+    fs::remove_file(temp_dir.path().join("small_file.txt"))?;
+    fs::write(temp_dir.path().join("empty_dir/new.txt"), vec![b'X'; 100])?;
+
+    let mut cmd = get_test_command();
+    cmd.arg("diff")
+        .arg(&snapshot)
+        .arg(temp_dir.path())
+        .arg("--json");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let diff: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(diff["total_delta"], 2600);
+
+    let changes: Vec<(&str, &str, i64)> = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["path"].as_str().unwrap(),
+                c["change"].as_str().unwrap(),
+                c["delta"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("subdir", "grew", 3000),
+            ("subdir/nested.txt", "grew", 3000),
+            ("small_file.txt", "removed", -500),
+            ("empty_dir", "grew", 100),
+            ("empty_dir/new.txt", "added", 100),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rescans_use_cache_unless_disabled() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let cache_home = TempDir::new()?;
+    let subdir_size = |args: &[&str]| -> Result<u64, Box<dyn std::error::Error>> {
+        let mut cmd = get_test_command();
+        cmd.env("XDG_CACHE_HOME", cache_home.path())
+            .arg(temp_dir.path())
+            .args(["--format", "json"])
+            .args(args);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let json: serde_json::Value = serde_json::from_slice(&output)?;
+        let subdir = json["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["path"].as_str().unwrap().ends_with("subdir"))
+            .unwrap();
+        Ok(subdir["size_bytes"].as_u64().unwrap())
+    };
+
+    // Untouched for a month, so not looked at again while its directory is unchanged
+    let nested = temp_dir.path().join("subdir/nested.txt");
+    let month_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 86400);
+    fs::File::options()
+        .write(true)
+        .open(&nested)?
+        .set_modified(month_ago)?;
+
+    assert_eq!(subdir_size(&[])?, 1000);
+    assert_eq!(
+        fs::read_dir(cache_home.path().join("disk-cleaner/scans"))?.count(),
+        1
+    );
+
+    // The rewritten file's directory is unchanged, so the cached listing is used
+    fs::write(&nested, vec![b'N'; 3000])?;
+    assert_eq!(subdir_size(&[])?, 1000);
+    assert_eq!(subdir_size(&["--no-cache"])?, 3000);
+
+    // Adding a file changes the directory, so it is read again
+    fs::write(temp_dir.path().join("subdir/more.txt"), vec![b'M'; 100])?;
+    assert_eq!(subdir_size(&[])?, 3100);
+    Ok(())
+}
+
+#[test]
+fn test_exclude_and_include_globs() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    fs::create_dir_all(temp_dir.path().join("subdir/.git/objects"))?;
+    fs::write(
+        temp_dir.path().join("subdir/.git/objects/pack"),
+        vec![b'G'; 7000],
+    )?;
+    let totals = |args: &[&str]| -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let mut cmd = get_test_command();
+        cmd.arg(temp_dir.path())
+            .args(["--format", "json", "--no-cache"])
+            .args(args);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let json: serde_json::Value = serde_json::from_slice(&output)?;
+        Ok(json["totals"].clone())
+    };
+
+    let all = totals(&[])?;
+    assert_eq!(all["apparent_bytes"], 15500);
+    assert!(all.get("excluded").is_none());
+
+    // The excluded directory is not walked; the excluded file is counted apart
+    let filtered = totals(&["--exclude", ".git", "--exclude", "large_*"])?;
+    assert_eq!(filtered["apparent_bytes"], 3500);
+    assert_eq!(filtered["excluded"]["directories"], 1);
+    assert_eq!(filtered["excluded"]["files"], 1);
+    assert_eq!(filtered["excluded"]["apparent_bytes"], 5000);
+
+    let included = totals(&["--include", "subdir", "--exclude", ".git"])?;
+    assert_eq!(included["apparent_bytes"], 1000);
+    assert_eq!(included["excluded"]["files"], 3);
+    assert_eq!(included["excluded"]["apparent_bytes"], 7500);
+
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path())
+        .args(["--exclude", "[", "--format", "json"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid glob '['"));
+    Ok(())
+}
+
+#[test]
+fn test_git_status_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_integration_test_structure()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    };
+    if !git(&["init", "-q"]) {
+        eprintln!("git is not available, skipping");
+        return Ok(());
+    }
+    fs::write(
+        temp_dir.path().join(".gitignore"),
+        "large_file.txt\nsubdir/\n",
+    )?;
+    assert!(git(&["add", ".gitignore", "medium_file.txt"]));
+
+    let statuses =
+        |args: &[&str]| -> Result<Vec<(String, serde_json::Value)>, Box<dyn std::error::Error>> {
+            let mut cmd = get_test_command();
+            cmd.arg(temp_dir.path())
+                .args(["--format", "json", "--no-cache"])
+                .args(args);
+            let output = cmd.assert().success().get_output().stdout.clone();
+            let json: serde_json::Value = serde_json::from_slice(&output)?;
+            Ok(json["entries"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| {
+                    let path = e["path"].as_str().unwrap();
+                    let name = path.rsplit('/').next().unwrap().to_string();
+                    (name, e["git"]["status"].clone())
+                })
+                .collect())
+        };
+
+    let tagged = statuses(&[])?;
+    let status = |name: &str| tagged.iter().find(|(n, _)| n == name).unwrap().1.clone();
+    assert_eq!(status("medium_file.txt"), "tracked");
+    assert_eq!(status("small_file.txt"), "untracked");
+    assert_eq!(status("large_file.txt"), "ignored");
+    assert_eq!(status("subdir"), "ignored");
+    assert_eq!(status(".git"), serde_json::Value::Null);
+
+    // Entries streamed as NDJSON are tagged the same way
+    let mut cmd = get_test_command();
+    cmd.arg(temp_dir.path())
+        .args(["--format", "ndjson", "--no-cache"]);
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+    let mut streamed = Vec::new();
+    for line in output.lines() {
+        let record: serde_json::Value = serde_json::from_str(line)?;
+        if record["type"] == "entry" {
+            let name = record["path"].as_str().unwrap().rsplit('/').next().unwrap();
+            streamed.push((name.to_string(), record["git"]["status"].clone()));
+        }
+    }
+    let mut sorted = tagged.clone();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    streamed.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(streamed, sorted);
+
+    assert!(statuses(&["--no-git"])?
+        .iter()
+        .all(|(_, status)| status.is_null()));
+    Ok(())
+}